@@ -1,7 +1,7 @@
 use cosmwasm_std::{
-    to_binary, Api, BankMsg, CanonicalAddr, CosmosMsg, Env, Extern, HandleResponse, HandleResult,
-    HumanAddr, InitResponse, InitResult, Querier, QueryResult, ReadonlyStorage, StdError,
-    StdResult, Storage, Uint128,
+    from_binary, log, to_binary, Api, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Env, Extern,
+    HandleResponse, HandleResult, HumanAddr, InitResponse, InitResult, LogAttribute, Querier,
+    QueryResult, ReadonlyStorage, StdError, StdResult, Storage, Uint128,
 };
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 
@@ -11,19 +11,32 @@ use secret_toolkit::{
     utils::{pad_handle_result, pad_query_result, HandleCallback, Query},
 };
 
+use crate::contract_info::ContractInfo;
 use crate::msg::{
-    BackgroundCount, HandleAnswer, HandleMsg, InitMsg, QueryAnswer, QueryMsg, ViewerInfo,
+    AirdropEntry, Allocation, BackgroundCount, BackgroundRemaining, BackgroundSupply, DutchAuction,
+    HandleAnswer, HandleMsg, InitMsg, Payee, PendingMint, Phase, PhaseAllocation, QueryAnswer,
+    QueryMsg, ReceiveMsg, TokenPrice, ViewerInfo,
 };
-use crate::rand::sha_256;
-use crate::server_msgs::{NewGenesResponse, ServerHandleMsg, ServerQueryMsg};
-use crate::snip721::{ImageInfo, Mint, SerialNumber, Snip721HandleMsg};
+use crate::rand::{extend_entropy, sha_256, Prng};
+use crate::server_msgs::{GeneInfo, NewGenesResponse, ServerHandleMsg, ServerQueryMsg};
+use crate::snip20::Snip20HandleMsg;
+use crate::snip721::{Extension, ImageInfo, Metadata, Mint, SerialNumber, Snip721HandleMsg};
 use crate::state::{
-    Config, CONFIG_KEY, MY_ADDRESS_KEY, PREFIX_REVOKED_PERMITS, PREFIX_VIEW_KEY, PRNG_SEED_KEY,
+    Commitment, Config, Epoch, EpochSeed, StorePayee, StoreTokenPrice, WhitelistEntry, CONFIG_KEY,
+    EPOCH_KEY, MY_ADDRESS_KEY, PHASES_KEY, PREFIX_COMMITS, PREFIX_EPOCH_SEEDS, PREFIX_MINTED,
+    PREFIX_REVOKED_PERMITS, PREFIX_USED_VOUCHERS, PREFIX_VIEW_KEY, PREFIX_WHITELIST, PRNG_SEED_KEY,
 };
-use crate::storage::{load, may_load, save};
+use crate::storage::{load, may_load, remove, save};
 use crate::viewing_key::{ViewingKey, VIEWING_KEY_SIZE};
+use crate::voucher::Voucher;
 
 pub const BLOCK_SIZE: usize = 256;
+/// max number of skulls minted in each BatchMintNft of an airdrop
+pub const AIRDROP_BATCH_SIZE: usize = 50;
+/// max number of skulls in one airdrop, which limits the size of its NewGenes query
+pub const MAX_AIRDROP_SIZE: usize = 100;
+/// max number of commitments processed by one claim
+pub const MAX_CLAIMS_PER_TX: usize = 10;
 
 ////////////////////////////////////// Init ///////////////////////////////////////
 /// Returns InitResult
@@ -48,15 +61,44 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
     let sender_raw = deps.api.canonical_address(&env.message.sender)?;
     let prng_seed: Vec<u8> = sha_256(base64::encode(msg.entropy.as_bytes()).as_bytes()).to_vec();
     save(&mut deps.storage, PRNG_SEED_KEY, &prng_seed)?;
+    save(
+        &mut deps.storage,
+        EPOCH_KEY,
+        &Epoch {
+            id: 0,
+            height: env.block.height,
+            committed: false,
+        },
+    )?;
     let vk = ViewingKey::new(&env, &prng_seed, msg.entropy.as_ref());
     let admins = vec![sender_raw];
+    if msg.reserve > msg.supply_cap {
+        return Err(StdError::generic_err(
+            "Reserve can not be larger than the supply cap",
+        ));
+    }
     let config = Config {
         nft_contract: msg.nft_contract.get_store(&deps.api)?,
         svg_contract: msg.svg_server.get_store(&deps.api)?,
         halt: true,
+        mint_start: None,
+        mint_end: None,
         multi_sig: deps.api.canonical_address(&msg.multi_sig)?,
+        payees: Vec::new(),
         mint_cnt: 0,
+        mint_price: msg.mint_price,
+        denom: msg.denom,
+        auction: None,
+        max_per_tx: msg.max_per_tx,
+        supply_cap: msg.supply_cap,
+        max_per_wallet: msg.max_per_wallet,
+        reserve: msg.reserve,
+        reserve_cnt: 0,
+        airdrop_cnt: 0,
+        voucher_cnt: 0,
+        token_prices: Vec::new(),
         backgd_cnts: Vec::new(),
+        backgrounds: Vec::new(),
         admins,
         viewing_key: vk.0,
     };
@@ -101,6 +143,9 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             backgrounds,
             entropy,
         } => try_mint(deps, env, backgrounds, entropy),
+        HandleMsg::Receive {
+            from, amount, msg, ..
+        } => try_receive(deps, env, from, amount, msg),
         HandleMsg::CreateViewingKey { entropy } => try_create_key(deps, &env, &entropy),
         HandleMsg::SetViewingKey { key, .. } => try_set_key(deps, &env.message.sender, key),
         HandleMsg::AddAdmins { admins } => try_add_admins(deps, &env.message.sender, &admins),
@@ -110,13 +155,64 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         }
         HandleMsg::NewMultiSig { address } => try_new_multi_sig(deps, &env.message.sender, address),
         HandleMsg::SetMintStatus { halt } => try_set_status(deps, &env.message.sender, halt),
+        HandleMsg::SetMintSchedule {
+            mint_start,
+            mint_end,
+        } => try_set_mint_schedule(deps, &env.message.sender, mint_start, mint_end),
+        HandleMsg::SetMintConfig {
+            mint_price,
+            denom,
+            max_per_tx,
+            supply_cap,
+            max_per_wallet,
+            reserve,
+        } => try_set_mint_config(
+            deps,
+            &env.message.sender,
+            mint_price,
+            denom,
+            max_per_tx,
+            supply_cap,
+            max_per_wallet,
+            reserve,
+        ),
+        HandleMsg::Claim {} => try_claim(deps, env),
+        HandleMsg::RedeemVoucher {
+            voucher,
+            backgrounds,
+            entropy,
+        } => try_redeem_voucher(deps, env, voucher, backgrounds, entropy),
+        HandleMsg::Airdrop { drops } => try_airdrop(deps, env, drops),
+        HandleMsg::ReserveMint { recipients } => try_reserve_mint(deps, env, recipients),
+        HandleMsg::SetDutchAuction { auction } => {
+            try_set_dutch_auction(deps, &env.message.sender, auction)
+        }
+        HandleMsg::SetPayees { payees } => try_set_payees(deps, &env.message.sender, payees),
+        HandleMsg::SetBackgrounds { backgrounds } => {
+            try_set_backgrounds(deps, &env.message.sender, backgrounds)
+        }
+        HandleMsg::SetTokenPrices { token_prices } => {
+            try_set_token_prices(deps, &env, token_prices)
+        }
+        HandleMsg::SetPhases { phases } => try_set_phases(deps, &env.message.sender, phases),
+        HandleMsg::AddWhitelist { phase, allocations } => {
+            try_add_whitelist(deps, &env.message.sender, &phase, allocations)
+        }
+        HandleMsg::RemoveWhitelist { phase, addresses } => {
+            try_remove_whitelist(deps, &env.message.sender, &phase, &addresses)
+        }
+        HandleMsg::RecoverMint {
+            owner,
+            token_id,
+            backgrounds,
+        } => try_recover_mint(deps, &env.message.sender, &owner, &token_id, backgrounds),
     };
     pad_handle_result(response, BLOCK_SIZE)
 }
 
 /// Returns HandleResult
 ///
-/// updates the minting status
+/// mints skulls paid for with the native coin
 ///
 /// # Arguments
 ///
@@ -129,6 +225,80 @@ fn try_mint<S: Storage, A: Api, Q: Querier>(
     env: Env,
     backgrounds: Vec<String>,
     entropy: String,
+) -> HandleResult {
+    let owner = env.message.sender.clone();
+    let payment = Payment::Native(env.message.sent_funds.clone());
+    mint_skulls(deps, &env, owner, backgrounds, entropy, payment)
+}
+
+/// Returns HandleResult
+///
+/// mints skulls paid for with an accepted snip20 token
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `from` - owner of the tokens that were sent
+/// * `amount` - amount of tokens that were sent
+/// * `msg` - optional base64 encoded ReceiveMsg
+fn try_receive<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    from: HumanAddr,
+    amount: Uint128,
+    msg: Option<Binary>,
+) -> HandleResult {
+    let rcv_msg: ReceiveMsg = from_binary(&msg.ok_or_else(|| {
+        StdError::generic_err("Receive msg must specify the backgrounds to mint")
+    })?)?;
+    match rcv_msg {
+        ReceiveMsg::Mint {
+            backgrounds,
+            entropy,
+        } => {
+            let payment = Payment::Token {
+                contract: env.message.sender.clone(),
+                amount,
+            };
+            mint_skulls(deps, &env, from, backgrounds, entropy, payment)
+        }
+    }
+}
+
+/// the form of payment sent for a mint
+pub enum Payment {
+    /// native coins sent with the tx
+    Native(Vec<Coin>),
+    /// snip20 tokens sent with a Receive
+    Token {
+        /// address of the snip20 contract
+        contract: HumanAddr,
+        /// amount of tokens received
+        amount: Uint128,
+    },
+}
+
+/// Returns HandleResult
+///
+/// verifies the payment, forwards it to the payees, and commits to minting skulls that
+/// can be claimed in a later block
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `owner` - address that will own the new skulls
+/// * `backgrounds` - list of backgrounds to mint with
+/// * `entropy` - entropy String for rng
+/// * `payment` - the Payment sent for the mint
+fn mint_skulls<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    owner: HumanAddr,
+    backgrounds: Vec<String>,
+    entropy: String,
+    payment: Payment,
 ) -> HandleResult {
     let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
     if config.halt {
@@ -136,107 +306,1348 @@ fn try_mint<S: Storage, A: Api, Q: Querier>(
             "The minter has been stopped.  No new tokens can be minted",
         ));
     }
-    // limited to 20 mints
-    let qty = backgrounds.len();
-    if qty > 20 {
-        return Err(StdError::generic_err(
-            "Only 20 Mystic Skulls may be minted at once",
-        ));
+    // only mint during the mint schedule
+    if config.mint_start.filter(|s| env.block.time < *s).is_some() {
+        return Err(StdError::generic_err("Minting has not started yet"));
+    }
+    if config.mint_end.filter(|e| env.block.time >= *e).is_some() {
+        return Err(StdError::generic_err("Minting has ended"));
     }
-    // stop minting at 10k
-    if (config.mint_cnt as usize) + qty > 10000 {
-        let remain = 10000 - config.mint_cnt;
+    // limited to the max number of mints per tx
+    let qty = backgrounds.len();
+    if qty > config.max_per_tx as usize {
         return Err(StdError::generic_err(format!(
-            "Only {} Mystic Skulls are known to be left in the SN graveyard",
-            remain
+            "Only {} Mystic Skulls may be minted at once",
+            config.max_per_tx
         )));
     }
-    // can't overflow if limited to 20
-    let price = Uint128(13000000 * (qty as u128));
-    if env.message.sent_funds.len() != 1
-        || env.message.sent_funds[0].amount != price
-        || env.message.sent_funds[0].denom != *"uscrt"
-    {
+    // stop minting at the supply cap, leaving room for the unminted reserve
+    check_supply(&config, qty)?;
+    // limit the number of skulls an address can mint over the life of the contract
+    let owner_raw = deps.api.canonical_address(&owner)?;
+    let mut minted_store = PrefixedStorage::new(PREFIX_MINTED, &mut deps.storage);
+    let minted: u16 = may_load(&minted_store, owner_raw.as_slice())?.unwrap_or(0);
+    let new_minted = minted.saturating_add(qty as u16);
+    if config.max_per_wallet != 0 && new_minted > config.max_per_wallet {
         return Err(StdError::generic_err(format!(
-            "You must pay exactly {} uscrt for {} Mystic Skulls",
-            price, qty
+            "{} may only mint {} more Mystic Skulls",
+            owner,
+            config.max_per_wallet.saturating_sub(minted)
         )));
     }
-    let ser_num = (config.mint_cnt as u32) + 1;
-    // update counts
-    config.mint_cnt += qty as u16;
-    for bg in backgrounds.iter() {
-        if let Some(bgc) = config.backgd_cnts.iter_mut().find(|b| b.background == *bg) {
-            bgc.count += 1;
-        } else {
-            config.backgd_cnts.push(BackgroundCount {
-                background: bg.clone(),
-                count: 1,
+    save(&mut minted_store, owner_raw.as_slice(), &new_minted)?;
+    // determine the active sale phase if sale phases are being used
+    let phases: Vec<Phase> = may_load(&deps.storage, PHASES_KEY)?.unwrap_or_else(Vec::new);
+    let phase = if phases.is_empty() {
+        None
+    } else {
+        Some(
+            phases
+                .into_iter()
+                .find(|p| phase_is_active(p, env.block.time))
+                .ok_or_else(|| StdError::generic_err("There is no sale phase currently active"))?,
+        )
+    };
+    // whitelisted addresses can only mint up to their remaining allocation
+    if let Some(ph) = phase.as_ref().filter(|p| p.whitelist_only) {
+        let mut wl_store =
+            PrefixedStorage::multilevel(&[PREFIX_WHITELIST, ph.name.as_bytes()], &mut deps.storage);
+        let mut entry: WhitelistEntry =
+            may_load(&wl_store, owner_raw.as_slice())?.unwrap_or(WhitelistEntry {
+                max_mints: 0,
+                used: 0,
             });
+        let remaining = entry.max_mints.saturating_sub(entry.used);
+        if (remaining as usize) < qty {
+            return Err(StdError::generic_err(format!(
+                "{} may only mint {} more Mystic Skulls during the {} phase",
+                owner, remaining, ph.name
+            )));
         }
+        entry.used += qty as u16;
+        save(&mut wl_store, owner_raw.as_slice(), &entry)?;
     }
-    save(&mut deps.storage, CONFIG_KEY, &config)?;
-    let viewer = ViewerInfo {
-        address: env.contract.address.clone(),
-        viewing_key: config.viewing_key.clone(),
+    // verify the payment, forward the price to the payees, and refund any excess
+    let (proceeds_msgs, amount_paid, refunded) = match payment {
+        Payment::Native(funds) => {
+            let unit_price = current_price(&config, phase.as_ref(), env.block.time);
+            let price = total_price(unit_price, qty)?;
+            let paid = funds
+                .first()
+                .filter(|c| funds.len() == 1 && c.denom == config.denom && c.amount >= price)
+                .map(|c| c.amount)
+                .ok_or_else(|| {
+                    StdError::generic_err(format!(
+                        "You must pay at least {} {} for {} Mystic Skulls",
+                        price, config.denom, qty
+                    ))
+                })?;
+            let mut msgs = split_proceeds(&deps.api, &config, price)?
+                .into_iter()
+                .map(|(payee, amount)| {
+                    CosmosMsg::Bank(BankMsg::Send {
+                        from_address: env.contract.address.clone(),
+                        to_address: payee,
+                        amount: vec![Coin {
+                            denom: config.denom.clone(),
+                            amount,
+                        }],
+                    })
+                })
+                .collect::<Vec<CosmosMsg>>();
+            // refund any overpayment
+            let excess = Uint128(paid.u128() - price.u128());
+            if excess.u128() > 0 {
+                msgs.push(CosmosMsg::Bank(BankMsg::Send {
+                    from_address: env.contract.address.clone(),
+                    to_address: owner.clone(),
+                    amount: vec![Coin {
+                        denom: config.denom.clone(),
+                        amount: excess,
+                    }],
+                }));
+            }
+            (msgs, paid, excess)
+        }
+        Payment::Token { contract, amount } => {
+            // token prices are fixed, so they can not honor a phase price or a dutch auction
+            // that is still lowering the native coin price
+            if phase.as_ref().and_then(|p| p.price).is_some()
+                || config
+                    .auction
+                    .as_ref()
+                    .filter(|a| auction_is_decaying(a, env.block.time))
+                    .is_some()
+            {
+                return Err(StdError::generic_err(
+                    "Tokens are not accepted as payment while a sale phase price or dutch auction is active",
+                ));
+            }
+            let contract_raw = deps.api.canonical_address(&contract)?;
+            let token = config
+                .token_prices
+                .iter()
+                .find(|t| t.token.address == contract_raw)
+                .ok_or_else(|| {
+                    StdError::generic_err(format!("{} is not an accepted payment token", contract))
+                })?;
+            let price = total_price(token.price, qty)?;
+            if amount < price {
+                return Err(StdError::generic_err(format!(
+                    "You must pay at least {} tokens for {} Mystic Skulls",
+                    price, qty
+                )));
+            }
+            let mut msgs = split_proceeds(&deps.api, &config, price)?
+                .into_iter()
+                .map(|(payee, amount)| {
+                    Snip20HandleMsg::Transfer {
+                        recipient: payee,
+                        amount,
+                        padding: None,
+                    }
+                    .to_cosmos_msg(
+                        token.token.code_hash.clone(),
+                        contract.clone(),
+                        None,
+                    )
+                })
+                .collect::<StdResult<Vec<CosmosMsg>>>()?;
+            // refund any overpayment
+            let excess = Uint128(amount.u128() - price.u128());
+            if excess.u128() > 0 {
+                msgs.push(
+                    Snip20HandleMsg::Transfer {
+                        recipient: owner.clone(),
+                        amount: excess,
+                        padding: None,
+                    }
+                    .to_cosmos_msg(
+                        token.token.code_hash.clone(),
+                        contract,
+                        None,
+                    )?,
+                );
+            }
+            (msgs, amount, excess)
+        }
     };
-    // get the genes
-    let svr_qry = ServerQueryMsg::NewGenes {
-        viewer,
-        height: env.block.height,
-        time: env.block.time,
-        sender: env.message.sender.clone(),
+    let token_ids = commit_mint(
+        &mut deps.storage,
+        env,
+        &mut config,
+        &owner,
+        &owner_raw,
+        backgrounds,
         entropy,
+    )?;
+
+    Ok(HandleResponse {
+        messages: proceeds_msgs,
+        log: token_ids.iter().map(|id| log("committed", id)).collect(),
+        data: Some(to_binary(&HandleAnswer::Mint {
+            skulls_minted: qty as u16,
+            token_ids,
+            amount_paid,
+            refunded,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// verifies an admin signed voucher and commits to minting its skulls without payment.
+/// The skulls can be claimed in a later block
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `voucher` - the admin signed Voucher
+/// * `backgrounds` - list of backgrounds to mint with
+/// * `entropy` - entropy String for rng
+fn try_redeem_voucher<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    voucher: Voucher,
+    backgrounds: Vec<String>,
+    entropy: String,
+) -> HandleResult {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if config.halt {
+        return Err(StdError::generic_err(
+            "The minter has been stopped.  No new tokens can be minted",
+        ));
+    }
+    if voucher.params.quantity == 0 {
+        return Err(StdError::generic_err(
+            "Voucher must be for at least one Mystic Skull",
+        ));
+    }
+    // only an admin can sign a voucher
+    let signer = voucher.signer(&deps.api, &env.block.chain_id)?;
+    if !config.admins.contains(&signer) {
+        return Err(StdError::generic_err("Voucher was not signed by an admin"));
+    }
+    let params = voucher.params;
+    if params.minter != env.contract.address {
+        return Err(StdError::generic_err(
+            "Voucher can not be redeemed with this minter",
+        ));
+    }
+    if params.recipient != env.message.sender {
+        return Err(StdError::generic_err(
+            "Voucher can only be redeemed by its recipient",
+        ));
+    }
+    if env.block.time >= params.expires {
+        return Err(StdError::generic_err("Voucher has expired"));
+    }
+    let qty = backgrounds.len();
+    if qty != params.quantity as usize {
+        return Err(StdError::generic_err(format!(
+            "Voucher must be redeemed for exactly {} Mystic Skulls",
+            params.quantity
+        )));
+    }
+    check_supply(&config, qty)?;
+    // prevent replays
+    let mut used_store = PrefixedStorage::new(PREFIX_USED_VOUCHERS, &mut deps.storage);
+    if may_load::<bool, _>(&used_store, params.voucher_id.as_bytes())?.is_some() {
+        return Err(StdError::generic_err(format!(
+            "Voucher {} has already been redeemed",
+            params.voucher_id
+        )));
+    }
+    save(&mut used_store, params.voucher_id.as_bytes(), &true)?;
+    config.voucher_cnt += qty as u16;
+    let owner_raw = deps.api.canonical_address(&params.recipient)?;
+    let token_ids = commit_mint(
+        &mut deps.storage,
+        &env,
+        &mut config,
+        &params.recipient,
+        &owner_raw,
         backgrounds,
-    };
-    let server = config.svg_contract.into_humanized(&deps.api)?;
-    let collection = config.nft_contract.into_humanized(&deps.api)?;
-    let svr_resp: NewGenesResponse = svr_qry.query(
-        &deps.querier,
-        server.code_hash.clone(),
-        server.address.clone(),
+        entropy,
     )?;
-    let mut genes: Vec<Vec<u8>> = Vec::new();
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: token_ids.iter().map(|id| log("committed", id)).collect(),
+        data: Some(to_binary(&HandleAnswer::RedeemVoucher {
+            skulls_minted: qty as u16,
+            token_ids,
+        })?),
+    })
+}
+
+/// Returns StdResult<()>
+///
+/// verifies there is enough supply left to mint without using the unminted reserve
+///
+/// # Arguments
+///
+/// * `config` - a reference to the Config
+/// * `qty` - number of skulls to mint
+fn check_supply(config: &Config, qty: usize) -> StdResult<()> {
+    let unminted_reserve = config.reserve - config.reserve_cnt;
+    if (config.mint_cnt as usize) + (unminted_reserve as usize) + qty > config.supply_cap as usize {
+        let remain = config
+            .supply_cap
+            .saturating_sub(config.mint_cnt)
+            .saturating_sub(unminted_reserve);
+        return Err(StdError::generic_err(format!(
+            "Only {} Mystic Skulls are known to be left in the SN graveyard",
+            remain
+        )));
+    }
+    Ok(())
+}
+
+/// Returns StdResult<Vec<String>>
+///
+/// reserves the serial numbers and commits to minting skulls that can be claimed once
+/// entropy has been added in a later block.  The commitment keeps a snapshot of the seed,
+/// and the genes are determined by it and the seed that closes its epoch.  Returns the
+/// token IDs of the new skulls
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `env` - a reference to the Env of contract's environment
+/// * `config` - a mutable reference to the Config
+/// * `owner` - a reference to the address that will own the new skulls
+/// * `owner_raw` - a reference to the owner's canonical address
+/// * `backgrounds` - list of backgrounds to mint with
+/// * `entropy` - entropy String for rng
+fn commit_mint<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    config: &mut Config,
+    owner: &HumanAddr,
+    owner_raw: &CanonicalAddr,
+    backgrounds: Vec<String>,
+    entropy: String,
+) -> StdResult<Vec<String>> {
+    // the skulls are claimed in a later tx, so only accept backgrounds known to be valid
+    check_backgrounds_set(config)?;
+    let serials = add_to_counts(config, &backgrounds)?;
+    save(storage, CONFIG_KEY, config)?;
+    let seed = update_seed(storage, env, owner, entropy.as_bytes())?;
+    let mut epoch: Epoch = load(storage, EPOCH_KEY)?;
+    if !epoch.committed {
+        epoch.committed = true;
+        save(storage, EPOCH_KEY, &epoch)?;
+    }
+    let token_ids: Vec<String> = serials.iter().map(|s| s.to_string()).collect();
+    let mut commit_store = PrefixedStorage::new(PREFIX_COMMITS, storage);
+    let mut commits: Vec<Commitment> =
+        may_load(&commit_store, owner_raw.as_slice())?.unwrap_or_else(Vec::new);
+    commits.push(Commitment {
+        serials,
+        backgrounds,
+        height: env.block.height,
+        epoch: epoch.id,
+        seed,
+        failed: false,
+    });
+    save(&mut commit_store, owner_raw.as_slice(), &commits)?;
+    Ok(token_ids)
+}
+
+/// Returns HandleResult
+///
+/// mints the sender's paid skulls whose epochs were closed in an earlier block.  The genes
+/// only depend on the seed saved with each commitment and the seed that closed its epoch,
+/// so they are the same no matter when the claim is made, and reverting a claim can not be
+/// used to roll them again.  The claim adds entropy, which can close the epoch of later
+/// commitments, so it succeeds even if nothing is ready
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+fn try_claim<S: Storage, A: Api, Q: Querier>(deps: &mut Extern<S, A, Q>, env: Env) -> HandleResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    update_seed(&mut deps.storage, &env, &env.message.sender, b"claim")?;
+    let commit_store = ReadonlyPrefixedStorage::new(PREFIX_COMMITS, &deps.storage);
+    let commits: Vec<Commitment> =
+        may_load(&commit_store, sender_raw.as_slice())?.unwrap_or_else(Vec::new);
+    // only claim commitments whose epochs were closed before this block
+    let epoch_store = ReadonlyPrefixedStorage::new(PREFIX_EPOCH_SEEDS, &deps.storage);
+    let mut ready: Vec<(Commitment, EpochSeed)> = Vec::new();
+    let mut pending: Vec<Commitment> = Vec::new();
+    for commit in commits.into_iter() {
+        // failed commitments wait for an admin to recover them
+        let closed = if ready.len() < MAX_CLAIMS_PER_TX && !commit.failed {
+            may_load::<EpochSeed, _>(&epoch_store, &commit.epoch.to_le_bytes())?
+                .filter(|e| e.height < env.block.height)
+        } else {
+            None
+        };
+        if let Some(epoch_seed) = closed {
+            ready.push((commit, epoch_seed));
+        } else {
+            pending.push(commit);
+        }
+    }
+    let server = config.svg_contract.get_humanized(&deps.api)?;
+    let collection = config.nft_contract.get_humanized(&deps.api)?;
+    let mut genes: Vec<GeneInfo> = Vec::new();
     let mut mints: Vec<Mint> = Vec::new();
-    let mut serial_number = SerialNumber {
-        mint_run: 1,
-        serial_number: ser_num,
-        quantity_minted_this_run: 10000,
-    };
-    for gene in svr_resp.new_genes.genes.into_iter() {
-        mints.push(Mint {
-            owner: env.message.sender.clone(),
-            public_metadata: None,
-            private_metadata: None,
-            serial_number: serial_number.clone(),
-            image_info: ImageInfo {
-                current: gene.current_image.clone(),
-                previous: gene.current_image,
-                natural: gene.genetic_image,
-                svg_server: None,
-            },
+    let mut token_ids: Vec<String> = Vec::new();
+    let mut failed: Vec<String> = Vec::new();
+    let mut ready_iter = ready.into_iter();
+    while let Some((mut commit, epoch_seed)) = ready_iter.next() {
+        // roll the genes as of the block that closed the epoch
+        let mut epoch_env = env.clone();
+        epoch_env.block.height = epoch_seed.height;
+        epoch_env.block.time = epoch_seed.time;
+        let mut entropy = commit.seed.clone();
+        entropy.extend_from_slice(&epoch_seed.seed);
+        // a commitment the svg server rejects is set aside so it does not block later ones
+        let new_genes = match query_genes(
+            deps,
+            &epoch_env,
+            &config,
+            &server,
+            commit.backgrounds.clone(),
+            env.message.sender.clone(),
+            None,
+            base64::encode(&sha_256(&entropy)),
+        ) {
+            Ok(genes) => genes,
+            Err(_) => {
+                failed.extend(commit.serials.iter().map(|s| s.to_string()));
+                commit.failed = true;
+                pending.push(commit);
+                continue;
+            }
+        };
+        // genes from separate queries do not know about each other, so leave a commitment
+        // with a twin for a later claim, when the earlier genes will have been recorded
+        if new_genes
+            .iter()
+            .any(|n| genes.iter().any(|g| g.unique_check == n.unique_check))
+        {
+            pending.push(commit);
+            pending.extend(ready_iter.by_ref().map(|(c, _)| c));
+            break;
+        }
+        for (gene, serial) in new_genes.into_iter().zip(commit.serials.into_iter()) {
+            token_ids.push(serial.to_string());
+            genes.push(gene.clone());
+            mints.push(skull_mint(
+                env.message.sender.clone(),
+                serial,
+                gene,
+                config.supply_cap,
+            ));
+        }
+    }
+    let mut commit_store = PrefixedStorage::new(PREFIX_COMMITS, &mut deps.storage);
+    if pending.is_empty() {
+        remove(&mut commit_store, sender_raw.as_slice());
+    } else {
+        save(&mut commit_store, sender_raw.as_slice(), &pending)?;
+    }
+    let qty = token_ids.len();
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    if !mints.is_empty() {
+        messages.push(Snip721HandleMsg::BatchMintNft { mints }.to_cosmos_msg(
+            collection.code_hash,
+            collection.address,
+            None,
+        )?);
+        messages.push(ServerHandleMsg::AddGenes { genes }.to_cosmos_msg(
+            server.code_hash,
+            server.address,
+            None,
+        )?);
+    }
+
+    let mut logs: Vec<LogAttribute> = token_ids.iter().map(|id| log("minted", id)).collect();
+    logs.extend(failed.iter().map(|id| log("failed", id)));
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::Claim {
+            skulls_claimed: qty as u16,
+            token_ids,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// lets a paid mint that failed to be claimed be claimed again, optionally replacing its
+/// backgrounds.  The genes still only depend on the mint's seeds, so recovering it can not
+/// be used to roll them again
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `owner` - a reference to the owner of the failed mint
+/// * `token_id` - token ID of any skull in the failed mint
+/// * `backgrounds` - optional replacement backgrounds
+fn try_recover_mint<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    owner: &HumanAddr,
+    token_id: &str,
+    backgrounds: Option<Vec<String>>,
+) -> HandleResult {
+    // only allow admins to do this
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !config.admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    let owner_raw = deps.api.canonical_address(owner)?;
+    let commit_store = ReadonlyPrefixedStorage::new(PREFIX_COMMITS, &deps.storage);
+    let mut commits: Vec<Commitment> =
+        may_load(&commit_store, owner_raw.as_slice())?.unwrap_or_else(Vec::new);
+    let commit = commits
+        .iter_mut()
+        .find(|c| c.serials.iter().any(|s| s.to_string() == token_id))
+        .ok_or_else(|| {
+            StdError::generic_err(format!(
+                "{} does not have a pending mint of token {}",
+                owner, token_id
+            ))
+        })?;
+    if !commit.failed {
+        return Err(StdError::generic_err(format!(
+            "The mint of token {} has not failed",
+            token_id
+        )));
+    }
+    commit.failed = false;
+    if let Some(new_bgs) = backgrounds {
+        if new_bgs.len() != commit.serials.len() {
+            return Err(StdError::generic_err(format!(
+                "Must provide {} replacement backgrounds",
+                commit.serials.len()
+            )));
+        }
+        check_backgrounds_set(&config)?;
+        // move the counts from the old backgrounds to the new ones
+        for bg in commit.backgrounds.iter() {
+            if let Some(bgc) = config.backgd_cnts.iter_mut().find(|b| b.background == *bg) {
+                bgc.count = bgc.count.saturating_sub(1);
+            }
+        }
+        config.backgd_cnts.retain(|b| b.count > 0);
+        add_background_counts(&mut config, &new_bgs)?;
+        commit.backgrounds = new_bgs;
+        save(&mut deps.storage, CONFIG_KEY, &config)?;
+    }
+    let mut commit_store = PrefixedStorage::new(PREFIX_COMMITS, &mut deps.storage);
+    save(&mut commit_store, owner_raw.as_slice(), &commits)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RecoverMint {
+            status: "success".to_string(),
+        })?),
+    })
+}
+
+/// Returns StdResult<Vec<(HumanAddr, Uint128)>>
+///
+/// splits the proceeds among the payees according to their shares.  Any remainder left
+/// from rounding goes to the first payee.  If there are no payees, everything goes to the
+/// multi sig
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `config` - a reference to the Config
+/// * `total` - the total proceeds to split
+fn split_proceeds<A: Api>(
+    api: &A,
+    config: &Config,
+    total: Uint128,
+) -> StdResult<Vec<(HumanAddr, Uint128)>> {
+    if config.payees.is_empty() {
+        return Ok(vec![(api.human_address(&config.multi_sig)?, total)]);
+    }
+    let mut splits = config
+        .payees
+        .iter()
+        .map(|p| {
+            Ok((
+                api.human_address(&p.address)?,
+                total.u128() * p.share as u128 / 10000,
+            ))
+        })
+        .collect::<StdResult<Vec<(HumanAddr, u128)>>>()?;
+    let distributed: u128 = splits.iter().map(|(_, amt)| amt).sum();
+    splits[0].1 += total.u128() - distributed;
+    Ok(splits
+        .into_iter()
+        .filter(|(_, amt)| *amt != 0)
+        .map(|(payee, amt)| (payee, Uint128(amt)))
+        .collect())
+}
+
+/// Returns HandleResult
+///
+/// mints skulls from the reserve without payment
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `recipients` - list of recipients and the backgrounds of the skulls they will receive
+fn try_reserve_mint<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    recipients: Vec<(HumanAddr, Vec<String>)>,
+) -> HandleResult {
+    // only allow admins to do this
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    if !config.admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    let mut owners: Vec<HumanAddr> = Vec::new();
+    let mut backgrounds: Vec<String> = Vec::new();
+    for (recipient, backs) in recipients.into_iter() {
+        owners.extend(std::iter::repeat(recipient).take(backs.len()));
+        backgrounds.extend(backs);
+    }
+    let qty = backgrounds.len();
+    if qty == 0 {
+        return Err(StdError::generic_err(
+            "At least one reserved Mystic Skull must be minted",
+        ));
+    }
+    let remain = config.reserve - config.reserve_cnt;
+    if qty > remain as usize {
+        return Err(StdError::generic_err(format!(
+            "Only {} reserved Mystic Skulls are left",
+            remain
+        )));
+    }
+    config.reserve_cnt += qty as u16;
+    let serials = add_to_counts(&mut config, &backgrounds)?;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+    // the svg server's prng seed and the block info provide the randomness
+    let (messages, token_ids) = mint_msgs(
+        deps,
+        &env,
+        &config,
+        serials,
+        owners,
+        backgrounds,
+        env.message.sender.clone(),
+        String::new(),
+    )?;
+
+    Ok(HandleResponse {
+        messages,
+        log: token_ids.iter().map(|id| log("minted", id)).collect(),
+        data: Some(to_binary(&HandleAnswer::ReserveMint {
+            skulls_minted: qty as u16,
+            token_ids,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// mints skulls directly to the listed owners without payment.  The skulls are minted in
+/// batches, and each recipient's genes are rolled with the recipient as the sender
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `drops` - list of the owners and backgrounds of the skulls to mint
+fn try_airdrop<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    drops: Vec<AirdropEntry>,
+) -> HandleResult {
+    // only allow admins to do this
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    if !config.admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    let qty = drops.len();
+    if qty == 0 {
+        return Err(StdError::generic_err("The airdrop list is empty"));
+    }
+    // every gene is rolled in one query, so larger snapshots must be split into several txs
+    if qty > MAX_AIRDROP_SIZE {
+        return Err(StdError::generic_err(format!(
+            "Only {} Mystic Skulls may be airdropped at once",
+            MAX_AIRDROP_SIZE
+        )));
+    }
+    // airdrops can not use the reserve
+    check_supply(&config, qty)?;
+    let backgrounds: Vec<String> = drops.iter().map(|d| d.background.clone()).collect();
+    let serials = add_to_counts(&mut config, &backgrounds)?;
+    config.airdrop_cnt += qty as u16;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+    let server = config.svg_contract.get_humanized(&deps.api)?;
+    let collection = config.nft_contract.get_humanized(&deps.api)?;
+    // roll every gene in one query so they are all unique, using each recipient as the
+    // sender of its own genes
+    let genes = query_genes(
+        deps,
+        &env,
+        &config,
+        &server,
+        backgrounds,
+        env.message.sender.clone(),
+        Some(drops.iter().map(|d| d.owner.clone()).collect()),
+        String::new(),
+    )?;
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    let token_ids: Vec<String> = serials.iter().map(|s| s.to_string()).collect();
+    for ((batch, batch_serials), batch_genes) in drops
+        .chunks(AIRDROP_BATCH_SIZE)
+        .zip(serials.chunks(AIRDROP_BATCH_SIZE))
+        .zip(genes.chunks(AIRDROP_BATCH_SIZE))
+    {
+        let mints: Vec<Mint> = batch
+            .iter()
+            .zip(batch_serials.iter())
+            .zip(batch_genes.iter())
+            .map(|((drop, serial), gene)| {
+                skull_mint(drop.owner.clone(), *serial, gene.clone(), config.supply_cap)
+            })
+            .collect();
+        messages.push(Snip721HandleMsg::BatchMintNft { mints }.to_cosmos_msg(
+            collection.code_hash.clone(),
+            collection.address.clone(),
+            None,
+        )?);
+    }
+    messages.push(ServerHandleMsg::AddGenes { genes }.to_cosmos_msg(
+        server.code_hash,
+        server.address,
+        None,
+    )?);
+
+    Ok(HandleResponse {
+        messages,
+        log: token_ids.iter().map(|id| log("minted", id)).collect(),
+        data: Some(to_binary(&HandleAnswer::Airdrop {
+            skulls_minted: qty as u16,
+            token_ids,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// sets the backgrounds that may be minted and their optional max supplies
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `backgrounds` - backgrounds that may be minted
+fn try_set_backgrounds<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    backgrounds: Vec<BackgroundSupply>,
+) -> HandleResult {
+    // only allow admins to do this
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !config.admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    for (idx, bg) in backgrounds.iter().enumerate() {
+        if backgrounds
+            .iter()
+            .skip(idx + 1)
+            .any(|b| b.background == bg.background)
+        {
+            return Err(StdError::generic_err(format!(
+                "Background:  {} is listed more than once",
+                bg.background
+            )));
+        }
+    }
+    config.backgrounds = backgrounds;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetBackgrounds {
+            backgrounds: config.backgrounds,
+        })?),
+    })
+}
+
+/// Returns StdResult<Vec<u32>>
+///
+/// updates the mint counts and returns the serial numbers of the new skulls.  The caller is
+/// responsible for saving the Config
+///
+/// # Arguments
+///
+/// * `config` - a mutable reference to the Config
+/// * `backgrounds` - list of the backgrounds of each new skull
+fn add_to_counts(config: &mut Config, backgrounds: &[String]) -> StdResult<Vec<u32>> {
+    let ser_num = (config.mint_cnt as u32) + 1;
+    config.mint_cnt += backgrounds.len() as u16;
+    add_background_counts(config, backgrounds)?;
+    Ok((ser_num..ser_num + backgrounds.len() as u32).collect())
+}
+
+/// Returns StdResult<()>
+///
+/// updates the background counts, and verifies registered backgrounds have not sold out.
+/// The caller is responsible for saving the Config
+///
+/// # Arguments
+///
+/// * `config` - a mutable reference to the Config
+/// * `backgrounds` - list of the backgrounds of each new skull
+fn add_background_counts(config: &mut Config, backgrounds: &[String]) -> StdResult<()> {
+    for bg in backgrounds.iter() {
+        let count = if let Some(bgc) = config.backgd_cnts.iter_mut().find(|b| b.background == *bg) {
+            bgc.count += 1;
+            bgc.count
+        } else {
+            config.backgd_cnts.push(BackgroundCount {
+                background: bg.clone(),
+                count: 1,
+            });
+            1
+        };
+        // only registered backgrounds that have not sold out can be minted
+        if !config.backgrounds.is_empty() {
+            let supply = config
+                .backgrounds
+                .iter()
+                .find(|b| b.background == *bg)
+                .ok_or_else(|| {
+                    StdError::generic_err(format!("{} is not an available background", bg))
+                })?;
+            if supply.max_supply.filter(|m| count > *m).is_some() {
+                return Err(StdError::generic_err(format!(
+                    "The {} background has sold out",
+                    bg
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns StdResult<()>
+///
+/// errors if the backgrounds that may be minted have not been set
+///
+/// # Arguments
+///
+/// * `config` - a reference to the Config
+fn check_backgrounds_set(config: &Config) -> StdResult<()> {
+    if config.backgrounds.is_empty() {
+        return Err(StdError::generic_err(
+            "The backgrounds that may be minted have not been set",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns StdResult<Vec<u8>>
+///
+/// mixes entropy into the contract's prng seed, saves it, and returns the new seed.  The
+/// first update in a later block closes the current epoch if any mints were committed in it
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `env` - a reference to the Env of contract's environment
+/// * `sender` - a reference to the address whose action is adding the entropy
+/// * `entropy` - entropy bytes to mix in
+fn update_seed<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    sender: &HumanAddr,
+    entropy: &[u8],
+) -> StdResult<Vec<u8>> {
+    let seed: Vec<u8> = load(storage, PRNG_SEED_KEY)?;
+    let rng_entropy = extend_entropy(env.block.height, env.block.time, sender, entropy);
+    let mut rng = Prng::new(&seed, &rng_entropy);
+    let new_seed = rng.rand_bytes().to_vec();
+    save(storage, PRNG_SEED_KEY, &new_seed)?;
+    let mut epoch: Epoch = load(storage, EPOCH_KEY)?;
+    if env.block.height > epoch.height {
+        if epoch.committed {
+            let mut epoch_store = PrefixedStorage::new(PREFIX_EPOCH_SEEDS, storage);
+            save(
+                &mut epoch_store,
+                &epoch.id.to_le_bytes(),
+                &EpochSeed {
+                    seed: new_seed.clone(),
+                    height: env.block.height,
+                    time: env.block.time,
+                },
+            )?;
+            epoch.id += 1;
+            epoch.committed = false;
+        }
+        epoch.height = env.block.height;
+        save(storage, EPOCH_KEY, &epoch)?;
+    }
+    Ok(new_seed)
+}
+
+/// Returns StdResult<(Vec<CosmosMsg>, Vec<String>)>
+///
+/// gets new genes from the svg server, and creates the messages to mint the skulls and
+/// record their genes.  Also returns the token IDs of the new skulls, which are their
+/// serial numbers
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `config` - a reference to the Config
+/// * `serials` - list of the serial numbers of each new skull
+/// * `owners` - list of the owners of each new skull
+/// * `backgrounds` - list of the backgrounds of each new skull
+/// * `sender` - address used to randomize the genes
+/// * `entropy` - entropy String for rng
+#[allow(clippy::too_many_arguments)]
+fn mint_msgs<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    config: &Config,
+    serials: Vec<u32>,
+    owners: Vec<HumanAddr>,
+    backgrounds: Vec<String>,
+    sender: HumanAddr,
+    entropy: String,
+) -> StdResult<(Vec<CosmosMsg>, Vec<String>)> {
+    let server = config.svg_contract.get_humanized(&deps.api)?;
+    let collection = config.nft_contract.get_humanized(&deps.api)?;
+    let new_genes = query_genes(
+        deps,
+        env,
+        config,
+        &server,
+        backgrounds,
+        sender,
+        None,
+        entropy,
+    )?;
+    let mut genes: Vec<GeneInfo> = Vec::new();
+    let mut mints: Vec<Mint> = Vec::new();
+    let mut token_ids: Vec<String> = Vec::new();
+    for ((gene, owner), serial) in new_genes
+        .into_iter()
+        .zip(owners.into_iter())
+        .zip(serials.into_iter())
+    {
+        token_ids.push(serial.to_string());
+        genes.push(gene.clone());
+        mints.push(skull_mint(owner, serial, gene, config.supply_cap));
+    }
+    let mint_msg = Snip721HandleMsg::BatchMintNft { mints };
+    let add_gene_msg = ServerHandleMsg::AddGenes { genes };
+    Ok((
+        vec![
+            mint_msg.to_cosmos_msg(collection.code_hash, collection.address, None)?,
+            add_gene_msg.to_cosmos_msg(server.code_hash, server.address, None)?,
+        ],
+        token_ids,
+    ))
+}
+
+/// Returns StdResult<Vec<GeneInfo>>
+///
+/// gets new genes from the svg server
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `config` - a reference to the Config
+/// * `server` - a reference to the svg server's code hash and address
+/// * `backgrounds` - list of the backgrounds of each new skull
+/// * `sender` - address used to randomize the genes
+/// * `senders` - optional address for each gene used to randomize it instead of the sender
+/// * `entropy` - entropy String for rng
+#[allow(clippy::too_many_arguments)]
+fn query_genes<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: &Env,
+    config: &Config,
+    server: &ContractInfo,
+    backgrounds: Vec<String>,
+    sender: HumanAddr,
+    senders: Option<Vec<HumanAddr>>,
+    entropy: String,
+) -> StdResult<Vec<GeneInfo>> {
+    let viewer = ViewerInfo {
+        address: env.contract.address.clone(),
+        viewing_key: config.viewing_key.clone(),
+    };
+    let svr_qry = ServerQueryMsg::NewGenes {
+        viewer,
+        height: env.block.height,
+        time: env.block.time,
+        sender,
+        entropy,
+        backgrounds,
+        senders,
+    };
+    let svr_resp: NewGenesResponse = svr_qry.query(
+        &deps.querier,
+        server.code_hash.clone(),
+        server.address.clone(),
+    )?;
+    Ok(svr_resp.new_genes.genes)
+}
+
+/// Returns Mint
+///
+/// creates the mint info for a new skull.  The token ID and name use the skull's serial
+/// number
+///
+/// # Arguments
+///
+/// * `owner` - owner of the new skull
+/// * `serial` - serial number of the new skull
+/// * `gene` - genetic image information of the new skull
+/// * `supply_cap` - max number of tokens that can be minted
+fn skull_mint(owner: HumanAddr, serial: u32, gene: GeneInfo, supply_cap: u16) -> Mint {
+    Mint {
+        token_id: Some(serial.to_string()),
+        owner,
+        public_metadata: Some(Metadata {
+            token_uri: None,
+            extension: Some(Extension {
+                name: Some(format!("Mystic Skulls #{}", serial)),
+                ..Extension::default()
+            }),
+        }),
+        private_metadata: None,
+        serial_number: SerialNumber {
+            mint_run: 1,
+            serial_number: serial,
+            quantity_minted_this_run: supply_cap as u32,
+        },
+        image_info: ImageInfo {
+            current: gene.current_image.clone(),
+            previous: gene.current_image,
+            natural: gene.genetic_image,
+            svg_server: None,
+        },
+    }
+}
+
+/// Returns bool
+///
+/// returns true if the sale phase is active at the specified time
+///
+/// # Arguments
+///
+/// * `phase` - a reference to the sale Phase
+/// * `time` - current block time in seconds since 01/01/1970
+fn phase_is_active(phase: &Phase, time: u64) -> bool {
+    phase.start.map_or(true, |s| s <= time) && phase.end.map_or(true, |e| time < e)
+}
+
+/// Returns HandleResult
+///
+/// sets the sale phases
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `phases` - the new sale phases
+fn try_set_phases<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    phases: Vec<Phase>,
+) -> HandleResult {
+    // only allow admins to do this
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !config.admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    for (idx, phase) in phases.iter().enumerate() {
+        if phases.iter().skip(idx + 1).any(|p| p.name == phase.name) {
+            return Err(StdError::generic_err(format!(
+                "Sale phase name:  {} is used more than once",
+                phase.name
+            )));
+        }
+        if let (Some(start), Some(end)) = (phase.start, phase.end) {
+            if start >= end {
+                return Err(StdError::generic_err(format!(
+                    "Sale phase:  {} must start before it ends",
+                    phase.name
+                )));
+            }
+        }
+    }
+    if phases.is_empty() {
+        remove(&mut deps.storage, PHASES_KEY);
+    } else {
+        save(&mut deps.storage, PHASES_KEY, &phases)?;
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetPhases { phases })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// adds addresses to a sale phase's whitelist or updates their allocations
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `phase` - name of the sale phase
+/// * `allocations` - addresses and the number of skulls they may mint
+fn try_add_whitelist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    phase: &str,
+    allocations: Vec<Allocation>,
+) -> HandleResult {
+    // only allow admins to do this
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !config.admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    check_phase_exists(&deps.storage, phase)?;
+    let raws = allocations
+        .iter()
+        .map(|a| deps.api.canonical_address(&a.address))
+        .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+    let mut wl_store =
+        PrefixedStorage::multilevel(&[PREFIX_WHITELIST, phase.as_bytes()], &mut deps.storage);
+    for (raw, alloc) in raws.iter().zip(allocations.iter()) {
+        // keep the count of skulls already minted so a new allocation does not reset it
+        let old: Option<WhitelistEntry> = may_load(&wl_store, raw.as_slice())?;
+        let used = old.map_or(0, |e| e.used);
+        save(
+            &mut wl_store,
+            raw.as_slice(),
+            &WhitelistEntry {
+                max_mints: alloc.max_mints,
+                used,
+            },
+        )?;
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::AddWhitelist {
+            status: "success".to_string(),
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// removes addresses from a sale phase's whitelist
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `phase` - name of the sale phase
+/// * `addresses` - addresses to remove
+fn try_remove_whitelist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    phase: &str,
+    addresses: &[HumanAddr],
+) -> HandleResult {
+    // only allow admins to do this
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !config.admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    check_phase_exists(&deps.storage, phase)?;
+    let raws = addresses
+        .iter()
+        .map(|a| deps.api.canonical_address(a))
+        .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+    let mut wl_store =
+        PrefixedStorage::multilevel(&[PREFIX_WHITELIST, phase.as_bytes()], &mut deps.storage);
+    for raw in raws.iter() {
+        // remember how many were minted in case the address is whitelisted again later
+        let entry: Option<WhitelistEntry> = may_load(&wl_store, raw.as_slice())?;
+        if let Some(mut entry) = entry {
+            if entry.used == 0 {
+                remove(&mut wl_store, raw.as_slice());
+            } else {
+                entry.max_mints = 0;
+                save(&mut wl_store, raw.as_slice(), &entry)?;
+            }
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RemoveWhitelist {
+            status: "success".to_string(),
+        })?),
+    })
+}
+
+/// Returns StdResult<()>
+///
+/// errors if there is no sale phase with the specified name
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `phase` - name of the sale phase
+fn check_phase_exists<S: ReadonlyStorage>(storage: &S, phase: &str) -> StdResult<()> {
+    let phases: Vec<Phase> = may_load(storage, PHASES_KEY)?.unwrap_or_else(Vec::new);
+    if !phases.iter().any(|p| p.name == phase) {
+        return Err(StdError::generic_err(format!(
+            "Sale phase:  {} does not exist",
+            phase
+        )));
+    }
+    Ok(())
+}
+
+/// Returns Uint128 of the native coin price of each skull at the specified time
+///
+/// # Arguments
+///
+/// * `config` - a reference to the Config
+/// * `phase` - optional reference to the active sale phase
+/// * `time` - time in seconds since 01/01/1970
+fn current_price(config: &Config, phase: Option<&Phase>, time: u64) -> Uint128 {
+    // a phase price takes precedence over the base price
+    if let Some(price) = phase.and_then(|p| p.price) {
+        return price;
+    }
+    if let Some(auction) = config.auction.as_ref() {
+        return auction_price(auction, time);
+    }
+    config.mint_price
+}
+
+/// Returns Uint128 of the dutch auction price of each skull at the specified time
+///
+/// # Arguments
+///
+/// * `auction` - a reference to the DutchAuction
+/// * `time` - time in seconds since 01/01/1970
+fn auction_price(auction: &DutchAuction, time: u64) -> Uint128 {
+    let steps = time.saturating_sub(auction.start) / auction.step_interval;
+    let decay = auction.decay_step.u128().saturating_mul(steps as u128);
+    let price = auction.start_price.u128().saturating_sub(decay);
+    Uint128(std::cmp::max(price, auction.end_price.u128()))
+}
+
+/// Returns bool
+///
+/// returns true if the dutch auction has started and has not reached its end price at the
+/// specified time
+///
+/// # Arguments
+///
+/// * `auction` - a reference to the DutchAuction
+/// * `time` - time in seconds since 01/01/1970
+fn auction_is_decaying(auction: &DutchAuction, time: u64) -> bool {
+    time >= auction.start && auction_price(auction, time) > auction.end_price
+}
+
+/// Returns StdResult<Uint128> of the total price of a number of skulls
+///
+/// # Arguments
+///
+/// * `price` - price of each skull
+/// * `qty` - number of skulls
+fn total_price(price: Uint128, qty: usize) -> StdResult<Uint128> {
+    price
+        .u128()
+        .checked_mul(qty as u128)
+        .map(Uint128)
+        .ok_or_else(|| StdError::generic_err("Mint price overflow"))
+}
+
+/// Returns HandleResult
+///
+/// sets the snip20 tokens accepted as payment and their prices
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `token_prices` - accepted snip20 tokens and their prices
+fn try_set_token_prices<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    token_prices: Vec<TokenPrice>,
+) -> HandleResult {
+    // only allow admins to do this
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    if !config.admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    let mut new_prices: Vec<StoreTokenPrice> = Vec::new();
+    for tp in token_prices.iter() {
+        let token = tp.token.get_store(&deps.api)?;
+        // register with any token that was not already accepted
+        if !config
+            .token_prices
+            .iter()
+            .any(|t| t.token.address == token.address)
+        {
+            let reg_msg = Snip20HandleMsg::RegisterReceive {
+                code_hash: env.contract_code_hash.clone(),
+                padding: None,
+            };
+            messages.push(reg_msg.to_cosmos_msg(
+                tp.token.code_hash.clone(),
+                tp.token.address.clone(),
+                None,
+            )?);
+        }
+        new_prices.push(StoreTokenPrice {
+            token,
+            price: tp.price,
         });
-        serial_number.serial_number += 1;
-        genes.push(gene.unique_check);
     }
-    let mint_msg = Snip721HandleMsg::BatchMintNft { mints };
-    let add_gene_msg = ServerHandleMsg::AddGenes { genes };
-    let messages: Vec<CosmosMsg> = vec![
-        mint_msg.to_cosmos_msg(collection.code_hash, collection.address, None)?,
-        add_gene_msg.to_cosmos_msg(server.code_hash, server.address, None)?,
-        CosmosMsg::Bank(BankMsg::Send {
-            from_address: env.contract.address,
-            to_address: deps.api.human_address(&config.multi_sig)?,
-            amount: env.message.sent_funds,
-        }),
-    ];
+    config.token_prices = new_prices;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
 
     Ok(HandleResponse {
         messages,
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::Mint {
-            skulls_minted: qty as u16,
-        })?),
+        data: Some(to_binary(&HandleAnswer::SetTokenPrices { token_prices })?),
     })
 }
 
@@ -275,6 +1686,155 @@ fn try_set_status<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Returns HandleResult
+///
+/// sets the times minting starts and ends
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `mint_start` - optional time minting starts in seconds since 01/01/1970
+/// * `mint_end` - optional time minting ends in seconds since 01/01/1970
+fn try_set_mint_schedule<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    mint_start: Option<u64>,
+    mint_end: Option<u64>,
+) -> HandleResult {
+    // only allow admins to do this
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !config.admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    if let (Some(start), Some(end)) = (mint_start, mint_end) {
+        if start >= end {
+            return Err(StdError::generic_err("Minting must start before it ends"));
+        }
+    }
+    config.mint_start = mint_start;
+    config.mint_end = mint_end;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetMintSchedule {
+            mint_start,
+            mint_end,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// sets the mint price, payment denomination, and mint limits
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `mint_price` - optional new price of each token
+/// * `denom` - optional new denomination of the native coin used for payment
+/// * `max_per_tx` - optional new max number of tokens that can be minted in one tx
+/// * `supply_cap` - optional new max number of tokens that can be minted
+/// * `max_per_wallet` - optional new max number of tokens an address can mint
+/// * `reserve` - optional new number of tokens reserved for admin mints
+#[allow(clippy::too_many_arguments)]
+fn try_set_mint_config<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    mint_price: Option<Uint128>,
+    denom: Option<String>,
+    max_per_tx: Option<u16>,
+    supply_cap: Option<u16>,
+    max_per_wallet: Option<u16>,
+    reserve: Option<u16>,
+) -> HandleResult {
+    // only allow admins to do this
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !config.admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    let mut save_it = false;
+    if let Some(price) = mint_price {
+        if config.mint_price != price {
+            config.mint_price = price;
+            save_it = true;
+        }
+    }
+    if let Some(dnm) = denom {
+        if config.denom != dnm {
+            config.denom = dnm;
+            save_it = true;
+        }
+    }
+    if let Some(max) = max_per_tx {
+        if config.max_per_tx != max {
+            config.max_per_tx = max;
+            save_it = true;
+        }
+    }
+    if let Some(cap) = supply_cap {
+        // can't lower the cap below what has already been minted
+        if cap < config.mint_cnt {
+            return Err(StdError::generic_err(format!(
+                "Supply cap can not be less than the {} tokens already minted",
+                config.mint_cnt
+            )));
+        }
+        if config.supply_cap != cap {
+            config.supply_cap = cap;
+            save_it = true;
+        }
+    }
+    if let Some(max) = max_per_wallet {
+        if config.max_per_wallet != max {
+            config.max_per_wallet = max;
+            save_it = true;
+        }
+    }
+    if let Some(rsv) = reserve {
+        // can't lower the reserve below what has already been minted from it
+        if rsv < config.reserve_cnt {
+            return Err(StdError::generic_err(format!(
+                "Reserve can not be less than the {} tokens already minted from it",
+                config.reserve_cnt
+            )));
+        }
+        if config.reserve != rsv {
+            config.reserve = rsv;
+            save_it = true;
+        }
+    }
+    // the unminted reserve must still fit under the supply cap
+    if (config.mint_cnt as u32) + ((config.reserve - config.reserve_cnt) as u32)
+        > config.supply_cap as u32
+    {
+        return Err(StdError::generic_err(
+            "Supply cap is too small to hold the remaining reserve",
+        ));
+    }
+    if save_it {
+        save(&mut deps.storage, CONFIG_KEY, &config)?;
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetMintConfig {
+            mint_price: config.mint_price,
+            denom: config.denom,
+            max_per_tx: config.max_per_tx,
+            supply_cap: config.supply_cap,
+            max_per_wallet: config.max_per_wallet,
+            reserve: config.reserve,
+        })?),
+    })
+}
+
 /// Returns HandleResult
 ///
 /// changes the multi sig address
@@ -311,6 +1871,103 @@ fn try_new_multi_sig<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Returns HandleResult
+///
+/// sets or removes the dutch auction price schedule
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `auction` - optional dutch auction price schedule
+fn try_set_dutch_auction<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    auction: Option<DutchAuction>,
+) -> HandleResult {
+    // only allow admins to do this
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !config.admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    if let Some(auc) = auction.as_ref() {
+        if auc.step_interval == 0 {
+            return Err(StdError::generic_err(
+                "The auction step interval must be greater than zero",
+            ));
+        }
+        if auc.start_price < auc.end_price {
+            return Err(StdError::generic_err(
+                "The auction start price can not be lower than the end price",
+            ));
+        }
+    }
+    config.auction = auction;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetDutchAuction {
+            auction: config.auction,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// sets the payees that split the mint proceeds
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `payees` - payees and their shares in basis points
+fn try_set_payees<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    payees: Vec<Payee>,
+) -> HandleResult {
+    // only allow admins to do this
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !config.admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    if !payees.is_empty() {
+        let total: u32 = payees.iter().map(|p| p.share as u32).sum();
+        if total != 10000 {
+            return Err(StdError::generic_err(format!(
+                "Payee shares must sum to 10000 basis points, but they sum to {}",
+                total
+            )));
+        }
+    }
+    let mut store_payees: Vec<StorePayee> = Vec::new();
+    for payee in payees.iter() {
+        let address = deps.api.canonical_address(&payee.address)?;
+        if store_payees.iter().any(|p| p.address == address) {
+            return Err(StdError::generic_err(format!(
+                "Payee:  {} is listed more than once",
+                payee.address
+            )));
+        }
+        store_payees.push(StorePayee {
+            address,
+            share: payee.share,
+        });
+    }
+    config.payees = store_payees;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetPayees { payees })?),
+    })
+}
+
 /// Returns HandleResult
 ///
 /// adds to the the admin list
@@ -486,12 +2143,21 @@ fn revoke_permit<S: Storage>(
 pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryMsg) -> QueryResult {
     let response = match msg {
         QueryMsg::MintStatus {} => query_status(&deps.storage),
+        QueryMsg::MintConfig {} => query_mint_config(&deps.storage),
+        QueryMsg::TokenPrices {} => query_token_prices(deps),
+        QueryMsg::Phases {} => query_phases(&deps.storage),
+        QueryMsg::CurrentPrice { time } => query_current_price(&deps.storage, time),
+        QueryMsg::BackgroundAvailability {} => query_background_availability(&deps.storage),
+        QueryMsg::MintedBy { viewer, permit } => query_minted_by(deps, viewer, permit),
+        QueryMsg::PendingMints { viewer, permit } => query_pending_mints(deps, viewer, permit),
+        QueryMsg::MyAllocation { viewer, permit } => query_my_allocation(deps, viewer, permit),
         QueryMsg::Admins { viewer, permit } => query_admins(deps, viewer, permit),
         QueryMsg::MintCounts { viewer, permit } => query_counts(deps, viewer, permit),
         QueryMsg::NftContract {} => query_nft_contract(deps),
         QueryMsg::NumMinted {} => query_num_minted(&deps.storage),
         QueryMsg::SvgServer { viewer, permit } => query_server(deps, viewer, permit),
         QueryMsg::MultiSig { viewer, permit } => query_multi_sig(deps, viewer, permit),
+        QueryMsg::Payees { viewer, permit } => query_payees(deps, viewer, permit),
     };
     pad_query_result(response, BLOCK_SIZE)
 }
@@ -515,6 +2181,34 @@ fn query_multi_sig<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Returns QueryResult displaying the payees that split the mint proceeds
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `viewer` - optional address and key making an authenticated query request
+/// * `permit` - optional permit with "owner" permission
+fn query_payees<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    viewer: Option<ViewerInfo>,
+    permit: Option<Permit>,
+) -> QueryResult {
+    // only allow admins to do this
+    let (config, _) = check_admin(deps, viewer, permit)?;
+    to_binary(&QueryAnswer::Payees {
+        payees: config
+            .payees
+            .iter()
+            .map(|p| {
+                Ok(Payee {
+                    address: deps.api.human_address(&p.address)?,
+                    share: p.share,
+                })
+            })
+            .collect::<StdResult<Vec<Payee>>>()?,
+    })
+}
+
 /// Returns QueryResult displaying the svg server contract information
 ///
 /// # Arguments
@@ -578,9 +2272,191 @@ fn query_status<S: ReadonlyStorage>(storage: &S) -> QueryResult {
     let config: Config = load(storage, CONFIG_KEY)?;
     to_binary(&QueryAnswer::MintStatus {
         minting_has_halted: config.halt,
+        mint_start: config.mint_start,
+        mint_end: config.mint_end,
+    })
+}
+
+/// Returns QueryResult displaying the mint price, payment denomination, and mint limits
+///
+/// # Arguments
+///
+/// * `storage` - reference to the contract's storage
+fn query_mint_config<S: ReadonlyStorage>(storage: &S) -> QueryResult {
+    let config: Config = load(storage, CONFIG_KEY)?;
+    to_binary(&QueryAnswer::MintConfig {
+        mint_price: config.mint_price,
+        denom: config.denom,
+        max_per_tx: config.max_per_tx,
+        supply_cap: config.supply_cap,
+        max_per_wallet: config.max_per_wallet,
+        reserve: config.reserve,
+    })
+}
+
+/// Returns QueryResult displaying the snip20 tokens accepted as payment and their prices
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+fn query_token_prices<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    to_binary(&QueryAnswer::TokenPrices {
+        token_prices: config
+            .token_prices
+            .into_iter()
+            .map(|t| {
+                Ok(TokenPrice {
+                    token: t.token.into_humanized(&deps.api)?,
+                    price: t.price,
+                })
+            })
+            .collect::<StdResult<Vec<TokenPrice>>>()?,
+    })
+}
+
+/// Returns QueryResult displaying the number of skulls still available with each background
+///
+/// # Arguments
+///
+/// * `storage` - reference to the contract's storage
+fn query_background_availability<S: ReadonlyStorage>(storage: &S) -> QueryResult {
+    let config: Config = load(storage, CONFIG_KEY)?;
+    let backgrounds = config
+        .backgrounds
+        .into_iter()
+        .map(|b| {
+            let minted = config
+                .backgd_cnts
+                .iter()
+                .find(|c| c.background == b.background)
+                .map_or(0, |c| c.count);
+            BackgroundRemaining {
+                remaining: b.max_supply.map(|m| m.saturating_sub(minted)),
+                background: b.background,
+                max_supply: b.max_supply,
+                minted,
+            }
+        })
+        .collect::<Vec<BackgroundRemaining>>();
+    to_binary(&QueryAnswer::BackgroundAvailability { backgrounds })
+}
+
+/// Returns QueryResult displaying the native coin price of each skull at the specified time
+///
+/// # Arguments
+///
+/// * `storage` - reference to the contract's storage
+/// * `time` - time in seconds since 01/01/1970
+fn query_current_price<S: ReadonlyStorage>(storage: &S, time: u64) -> QueryResult {
+    let config: Config = load(storage, CONFIG_KEY)?;
+    let phases: Vec<Phase> = may_load(storage, PHASES_KEY)?.unwrap_or_else(Vec::new);
+    let phase = phases.iter().find(|p| phase_is_active(p, time));
+    to_binary(&QueryAnswer::CurrentPrice {
+        price: current_price(&config, phase, time),
+        denom: config.denom,
+        auction: config.auction,
+    })
+}
+
+/// Returns QueryResult displaying the sale phases
+///
+/// # Arguments
+///
+/// * `storage` - reference to the contract's storage
+fn query_phases<S: ReadonlyStorage>(storage: &S) -> QueryResult {
+    let phases: Vec<Phase> = may_load(storage, PHASES_KEY)?.unwrap_or_else(Vec::new);
+    to_binary(&QueryAnswer::Phases { phases })
+}
+
+/// Returns QueryResult displaying the number of skulls the querier has minted
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `viewer` - optional address and key making an authenticated query request
+/// * `permit` - optional permit with "owner" permission
+fn query_minted_by<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    viewer: Option<ViewerInfo>,
+    permit: Option<Permit>,
+) -> QueryResult {
+    let (querier, _) = get_querier(deps, viewer, permit)?;
+    let minted_store = ReadonlyPrefixedStorage::new(PREFIX_MINTED, &deps.storage);
+    let minted: u16 = may_load(&minted_store, querier.as_slice())?.unwrap_or(0);
+    to_binary(&QueryAnswer::MintedBy { minted })
+}
+
+/// Returns QueryResult displaying the querier's paid mints that have not been claimed
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `viewer` - optional address and key making an authenticated query request
+/// * `permit` - optional permit with "owner" permission
+fn query_pending_mints<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    viewer: Option<ViewerInfo>,
+    permit: Option<Permit>,
+) -> QueryResult {
+    let (querier, _) = get_querier(deps, viewer, permit)?;
+    let commit_store = ReadonlyPrefixedStorage::new(PREFIX_COMMITS, &deps.storage);
+    let commits: Vec<Commitment> =
+        may_load(&commit_store, querier.as_slice())?.unwrap_or_else(Vec::new);
+    let epoch_store = ReadonlyPrefixedStorage::new(PREFIX_EPOCH_SEEDS, &deps.storage);
+    to_binary(&QueryAnswer::PendingMints {
+        pending: commits
+            .into_iter()
+            .map(|c| {
+                Ok(PendingMint {
+                    token_ids: c.serials.iter().map(|s| s.to_string()).collect(),
+                    claimable_after: may_load::<EpochSeed, _>(
+                        &epoch_store,
+                        &c.epoch.to_le_bytes(),
+                    )?
+                    .map(|e| e.height),
+                    backgrounds: c.backgrounds,
+                    height: c.height,
+                    failed: c.failed,
+                })
+            })
+            .collect::<StdResult<Vec<PendingMint>>>()?,
     })
 }
 
+/// Returns QueryResult displaying the querier's remaining whitelist allocations
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `viewer` - optional address and key making an authenticated query request
+/// * `permit` - optional permit with "owner" permission
+fn query_my_allocation<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    viewer: Option<ViewerInfo>,
+    permit: Option<Permit>,
+) -> QueryResult {
+    let (querier, _) = get_querier(deps, viewer, permit)?;
+    let phases: Vec<Phase> = may_load(&deps.storage, PHASES_KEY)?.unwrap_or_else(Vec::new);
+    let allocations = phases
+        .into_iter()
+        .filter(|p| p.whitelist_only)
+        .map(|p| {
+            let wl_store = ReadonlyPrefixedStorage::multilevel(
+                &[PREFIX_WHITELIST, p.name.as_bytes()],
+                &deps.storage,
+            );
+            let entry: Option<WhitelistEntry> = may_load(&wl_store, querier.as_slice())?;
+            let remaining = entry.map_or(0, |e| e.max_mints.saturating_sub(e.used));
+            Ok(PhaseAllocation {
+                phase: p.name,
+                remaining,
+            })
+        })
+        .collect::<StdResult<Vec<PhaseAllocation>>>()?;
+    to_binary(&QueryAnswer::MyAllocation { allocations })
+}
+
 /// Returns QueryResult displaying the total mint count
 ///
 /// # Arguments
@@ -609,6 +2485,10 @@ fn query_counts<S: Storage, A: Api, Q: Querier>(
     let (config, _) = check_admin(deps, viewer, permit)?;
     to_binary(&QueryAnswer::MintCounts {
         total: config.mint_cnt,
+        reserved: config.reserve_cnt,
+        airdropped: config.airdrop_cnt,
+        vouchers: config.voucher_cnt,
+        reserve_remaining: config.reserve - config.reserve_cnt,
         by_background: config.backgd_cnts,
     })
 }
@@ -4,11 +4,14 @@ mod contract_info;
 pub mod msg;
 mod rand;
 mod server_msgs;
+mod snip20;
 mod snip721;
 pub mod state;
 mod storage;
+mod unittest_handles;
 mod utils;
 mod viewing_key;
+mod voucher;
 
 #[cfg(target_arch = "wasm32")]
 mod wasm {
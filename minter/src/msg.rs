@@ -1,5 +1,6 @@
 use crate::contract_info::ContractInfo;
-use cosmwasm_std::HumanAddr;
+use crate::voucher::Voucher;
+use cosmwasm_std::{Binary, HumanAddr, Uint128};
 use schemars::JsonSchema;
 use secret_toolkit::permit::Permit;
 use serde::{Deserialize, Serialize};
@@ -13,6 +14,19 @@ pub struct InitMsg {
     pub svg_server: ContractInfo,
     /// address of the multisig
     pub multi_sig: HumanAddr,
+    /// price of each token
+    pub mint_price: Uint128,
+    /// denomination of the native coin used for payment
+    pub denom: String,
+    /// max number of tokens that can be minted in one tx
+    pub max_per_tx: u16,
+    /// max number of tokens that can be minted
+    pub supply_cap: u16,
+    /// max number of tokens an address can mint over the life of the contract.  0 means
+    /// there is no limit
+    pub max_per_wallet: u16,
+    /// number of tokens (included in the supply cap) reserved for admin mints
+    pub reserve: u16,
     /// entropy used for prng seed
     pub entropy: String,
 }
@@ -28,6 +42,17 @@ pub enum HandleMsg {
         /// entropy used for rng
         entropy: String,
     },
+    /// Receive snip20 tokens to pay for a mint
+    Receive {
+        /// address that sent the tokens
+        sender: HumanAddr,
+        /// owner of the tokens sent
+        from: HumanAddr,
+        /// amount of tokens sent
+        amount: Uint128,
+        /// ReceiveMsg specifying what to do with the tokens
+        msg: Option<Binary>,
+    },
     /// Create a viewing key
     CreateViewingKey { entropy: String },
     /// Set a viewing key
@@ -56,6 +81,108 @@ pub enum HandleMsg {
         /// true if minting should be halted
         halt: bool,
     },
+    /// set the times minting starts and ends.  This replaces the existing schedule
+    SetMintSchedule {
+        /// optional time minting starts in seconds since 01/01/1970
+        mint_start: Option<u64>,
+        /// optional time minting ends in seconds since 01/01/1970
+        mint_end: Option<u64>,
+    },
+    /// set the mint price, payment denomination, and mint limits
+    SetMintConfig {
+        /// optional new price of each token
+        mint_price: Option<Uint128>,
+        /// optional new denomination of the native coin used for payment
+        denom: Option<String>,
+        /// optional new max number of tokens that can be minted in one tx
+        max_per_tx: Option<u16>,
+        /// optional new max number of tokens that can be minted
+        supply_cap: Option<u16>,
+        /// optional new max number of tokens an address can mint over the life of the
+        /// contract.  0 means there is no limit
+        max_per_wallet: Option<u16>,
+        /// optional new number of tokens (included in the supply cap) reserved for admin
+        /// mints
+        reserve: Option<u16>,
+    },
+    /// set or remove the dutch auction price schedule
+    SetDutchAuction {
+        /// optional dutch auction price schedule.  If not provided, the auction is removed
+        /// and the fixed mint price is used
+        auction: Option<DutchAuction>,
+    },
+    /// set the payees that split the mint proceeds.  This replaces the existing list.  If
+    /// the list is empty, all proceeds are sent to the multi sig
+    SetPayees {
+        /// payees and their shares in basis points.  Shares must sum to 10,000
+        payees: Vec<Payee>,
+    },
+    /// set the backgrounds that may be minted and their optional max supplies.  This
+    /// replaces the existing list.  If the list is empty, any background may be minted
+    SetBackgrounds {
+        /// backgrounds that may be minted
+        backgrounds: Vec<BackgroundSupply>,
+    },
+    /// mint all the paid skulls that were committed in an earlier block
+    Claim {},
+    /// redeem an admin signed voucher to mint skulls without payment.  The skulls can be
+    /// claimed in a later block
+    RedeemVoucher {
+        /// the admin signed voucher
+        voucher: Voucher,
+        /// list of backgrounds to mint.  There must be one for each skull the voucher
+        /// can mint
+        backgrounds: Vec<String>,
+        /// entropy used for rng
+        entropy: String,
+    },
+    /// allows an admin to mint skulls directly to the listed owners without payment.  A
+    /// snapshot larger than the max airdrop size must be split into several airdrops
+    Airdrop {
+        /// list of the owners and backgrounds of the skulls to mint
+        drops: Vec<AirdropEntry>,
+    },
+    /// allows an admin to mint skulls from the reserve without payment
+    ReserveMint {
+        /// list of recipients and the backgrounds of the skulls they will receive
+        recipients: Vec<(HumanAddr, Vec<String>)>,
+    },
+    /// set the snip20 tokens accepted as payment and their prices.  This replaces the
+    /// existing list.  Tokens are not accepted while a sale phase price is active or while
+    /// a dutch auction is lowering the price
+    SetTokenPrices {
+        /// accepted snip20 tokens and the price of each skull in that token
+        token_prices: Vec<TokenPrice>,
+    },
+    /// set the sale phases.  This replaces the existing list
+    SetPhases {
+        /// sale phases in order of precedence
+        phases: Vec<Phase>,
+    },
+    /// add addresses to a sale phase's whitelist or update their allocations
+    AddWhitelist {
+        /// name of the sale phase
+        phase: String,
+        /// addresses and the number of skulls they may mint during the phase
+        allocations: Vec<Allocation>,
+    },
+    /// remove addresses from a sale phase's whitelist
+    RemoveWhitelist {
+        /// name of the sale phase
+        phase: String,
+        /// addresses to remove
+        addresses: Vec<HumanAddr>,
+    },
+    /// allows an admin to let a paid mint that failed to be claimed be claimed again,
+    /// optionally with new backgrounds
+    RecoverMint {
+        /// owner of the failed mint
+        owner: HumanAddr,
+        /// token ID of any skull in the failed mint
+        token_id: String,
+        /// optional replacement backgrounds.  There must be one for each skull in the mint
+        backgrounds: Option<Vec<String>>,
+    },
     /// disallow the use of a permit
     RevokePermit {
         /// name of the permit that is no longer valid
@@ -63,6 +190,19 @@ pub enum HandleMsg {
     },
 }
 
+/// messages that can be sent with a snip20 Send
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Mint tokens
+    Mint {
+        /// list of backgrounds to mint
+        backgrounds: Vec<String>,
+        /// entropy used for rng
+        entropy: String,
+    },
+}
+
 /// Responses from handle functions
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -85,12 +225,102 @@ pub enum HandleAnswer {
         /// true if minting has halted
         minting_has_halted: bool,
     },
+    /// response of setting the mint schedule
+    SetMintSchedule {
+        /// optional time minting starts in seconds since 01/01/1970
+        mint_start: Option<u64>,
+        /// optional time minting ends in seconds since 01/01/1970
+        mint_end: Option<u64>,
+    },
     RevokePermit {
         status: String,
     },
     /// response of minting skulls
     Mint {
         skulls_minted: u16,
+        /// token IDs of the new skulls
+        token_ids: Vec<String>,
+        /// amount sent as payment
+        amount_paid: Uint128,
+        /// amount of the payment that was refunded
+        refunded: Uint128,
+    },
+    /// response from redeeming a voucher
+    RedeemVoucher {
+        skulls_minted: u16,
+        /// token IDs of the new skulls
+        token_ids: Vec<String>,
+    },
+    /// response from claiming paid skulls
+    Claim {
+        skulls_claimed: u16,
+        /// token IDs of the new skulls
+        token_ids: Vec<String>,
+    },
+    /// response of airdropping skulls
+    Airdrop {
+        skulls_minted: u16,
+        /// token IDs of the new skulls
+        token_ids: Vec<String>,
+    },
+    /// response from setting the dutch auction price schedule
+    SetDutchAuction {
+        /// dutch auction price schedule if one is used
+        auction: Option<DutchAuction>,
+    },
+    /// response from setting the payees
+    SetPayees {
+        /// payees and their shares in basis points
+        payees: Vec<Payee>,
+    },
+    /// response from setting the backgrounds that may be minted
+    SetBackgrounds {
+        /// backgrounds that may be minted
+        backgrounds: Vec<BackgroundSupply>,
+    },
+    /// response of minting skulls from the reserve
+    ReserveMint {
+        skulls_minted: u16,
+        /// token IDs of the new skulls
+        token_ids: Vec<String>,
+    },
+    /// response from setting the mint config
+    SetMintConfig {
+        /// price of each token
+        mint_price: Uint128,
+        /// denomination of the native coin used for payment
+        denom: String,
+        /// max number of tokens that can be minted in one tx
+        max_per_tx: u16,
+        /// max number of tokens that can be minted
+        supply_cap: u16,
+        /// max number of tokens an address can mint over the life of the contract.  0
+        /// means there is no limit
+        max_per_wallet: u16,
+        /// number of tokens (included in the supply cap) reserved for admin mints
+        reserve: u16,
+    },
+    /// response from setting the accepted snip20 tokens
+    SetTokenPrices {
+        /// accepted snip20 tokens and the price of each skull in that token
+        token_prices: Vec<TokenPrice>,
+    },
+    /// response from setting the sale phases
+    SetPhases {
+        /// sale phases in order of precedence
+        phases: Vec<Phase>,
+    },
+    /// response from adding to a whitelist
+    AddWhitelist {
+        status: String,
+    },
+    /// response from removing from a whitelist
+    RemoveWhitelist {
+        status: String,
+    },
+    /// response from recovering a failed mint
+    RecoverMint {
+        status: String,
     },
 }
 
@@ -100,6 +330,44 @@ pub enum HandleAnswer {
 pub enum QueryMsg {
     /// display the minting status
     MintStatus {},
+    /// display the mint price, payment denomination, and mint limits
+    MintConfig {},
+    /// display the snip20 tokens accepted as payment and their prices
+    TokenPrices {},
+    /// display the sale phases
+    Phases {},
+    /// display the native coin price of each skull at the specified time.  Queries do not
+    /// have access to the block time, so it must be provided
+    CurrentPrice {
+        /// time in seconds since 01/01/1970
+        time: u64,
+    },
+    /// display the number of skulls still available with each background
+    BackgroundAvailability {},
+    /// display the number of skulls the querier has minted
+    MintedBy {
+        /// optional address and viewing key of the querier
+        viewer: Option<ViewerInfo>,
+        /// optional permit used to verify the querier's identity.  If both viewer and
+        /// permit are provided, the viewer will be ignored
+        permit: Option<Permit>,
+    },
+    /// display the querier's paid mints that have not been claimed
+    PendingMints {
+        /// optional address and viewing key of the querier
+        viewer: Option<ViewerInfo>,
+        /// optional permit used to verify the querier's identity.  If both viewer and
+        /// permit are provided, the viewer will be ignored
+        permit: Option<Permit>,
+    },
+    /// display the querier's remaining whitelist allocations
+    MyAllocation {
+        /// optional address and viewing key of the querier
+        viewer: Option<ViewerInfo>,
+        /// optional permit used to verify the querier's identity.  If both viewer and
+        /// permit are provided, the viewer will be ignored
+        permit: Option<Permit>,
+    },
     /// display the admin addresses
     Admins {
         /// optional address and viewing key of an admin
@@ -136,6 +404,14 @@ pub enum QueryMsg {
         /// are provided, the viewer will be ignored
         permit: Option<Permit>,
     },
+    /// display the payees that split the mint proceeds
+    Payees {
+        /// optional address and viewing key of an admin
+        viewer: Option<ViewerInfo>,
+        /// optional permit used to verify admin identity.  If both viewer and permit
+        /// are provided, the viewer will be ignored
+        permit: Option<Permit>,
+    },
 }
 
 /// responses to queries
@@ -151,11 +427,23 @@ pub enum QueryAnswer {
     MintStatus {
         /// true if minting has halted
         minting_has_halted: bool,
+        /// optional time minting starts in seconds since 01/01/1970
+        mint_start: Option<u64>,
+        /// optional time minting ends in seconds since 01/01/1970
+        mint_end: Option<u64>,
     },
     /// displays the mint counts
     MintCounts {
         /// total mint count
         total: u16,
+        /// number of skulls minted from the reserve
+        reserved: u16,
+        /// number of skulls airdropped
+        airdropped: u16,
+        /// number of skulls minted with vouchers
+        vouchers: u16,
+        /// number of reserved skulls that have not been minted
+        reserve_remaining: u16,
         /// mint counts broken down by background variant
         by_background: Vec<BackgroundCount>,
     },
@@ -167,6 +455,161 @@ pub enum QueryAnswer {
     SvgServer { svg_server: ContractInfo },
     /// displays the multi sig address
     MultiSig { address: HumanAddr },
+    /// displays the payees that split the mint proceeds
+    Payees {
+        /// payees and their shares in basis points.  If empty, all proceeds are sent
+        /// to the multi sig
+        payees: Vec<Payee>,
+    },
+    /// displays the mint price, payment denomination, and mint limits
+    MintConfig {
+        /// price of each token
+        mint_price: Uint128,
+        /// denomination of the native coin used for payment
+        denom: String,
+        /// max number of tokens that can be minted in one tx
+        max_per_tx: u16,
+        /// max number of tokens that can be minted
+        supply_cap: u16,
+        /// max number of tokens an address can mint over the life of the contract.  0
+        /// means there is no limit
+        max_per_wallet: u16,
+        /// number of tokens (included in the supply cap) reserved for admin mints
+        reserve: u16,
+    },
+    /// displays the snip20 tokens accepted as payment and their prices
+    TokenPrices {
+        /// accepted snip20 tokens and the price of each skull in that token
+        token_prices: Vec<TokenPrice>,
+    },
+    /// displays the number of skulls still available with each background
+    BackgroundAvailability {
+        /// availability of each background that may be minted
+        backgrounds: Vec<BackgroundRemaining>,
+    },
+    /// displays the native coin price of each skull
+    CurrentPrice {
+        /// price of each token
+        price: Uint128,
+        /// denomination of the native coin used for payment
+        denom: String,
+        /// dutch auction price schedule if one is used
+        auction: Option<DutchAuction>,
+    },
+    /// displays the sale phases
+    Phases {
+        /// sale phases in order of precedence
+        phases: Vec<Phase>,
+    },
+    /// displays the number of skulls the querier has minted
+    MintedBy {
+        /// number of skulls minted
+        minted: u16,
+    },
+    /// displays the querier's paid mints that have not been claimed
+    PendingMints {
+        /// paid mints that have not been claimed
+        pending: Vec<PendingMint>,
+    },
+    /// displays the querier's remaining whitelist allocations
+    MyAllocation {
+        /// remaining allocations for each whitelist only sale phase
+        allocations: Vec<PhaseAllocation>,
+    },
+}
+
+/// a sale phase
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct Phase {
+    /// name of the phase
+    pub name: String,
+    /// optional time the phase starts in seconds since 01/01/1970
+    pub start: Option<u64>,
+    /// optional time the phase ends in seconds since 01/01/1970
+    pub end: Option<u64>,
+    /// optional native coin price of each skull during this phase.  Defaults to the
+    /// mint config price
+    pub price: Option<Uint128>,
+    /// true if only whitelisted addresses may mint during this phase
+    pub whitelist_only: bool,
+}
+
+/// number of skulls an address may mint during a whitelist only phase
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct Allocation {
+    /// whitelisted address
+    pub address: HumanAddr,
+    /// total number of skulls it may mint during the phase, including any already minted
+    pub max_mints: u16,
+}
+
+/// remaining whitelist allocation for a sale phase
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct PhaseAllocation {
+    /// name of the sale phase
+    pub phase: String,
+    /// number of skulls that may still be minted during the phase
+    pub remaining: u16,
+}
+
+/// price of each skull when paying with a snip20 token
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct TokenPrice {
+    /// code hash and address of the snip20 contract
+    pub token: ContractInfo,
+    /// price of each skull in the token's smallest denomination
+    pub price: Uint128,
+}
+
+/// a skull to airdrop
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct AirdropEntry {
+    /// owner of the new skull
+    pub owner: HumanAddr,
+    /// background of the new skull
+    pub background: String,
+}
+
+/// a paid mint that has not been claimed
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct PendingMint {
+    /// token IDs the skulls will have
+    pub token_ids: Vec<String>,
+    /// backgrounds of the skulls
+    pub backgrounds: Vec<String>,
+    /// block height of the mint
+    pub height: u64,
+    /// block height in which entropy was added after the mint.  The skulls can be claimed
+    /// in any later block.  If None, entropy has not been added yet, which any mint or
+    /// claim will do
+    pub claimable_after: Option<u64>,
+    /// true if the skulls could not be claimed.  An admin must recover the mint before it
+    /// can be claimed again
+    pub failed: bool,
+}
+
+/// a dutch auction price schedule for native coin payments
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct DutchAuction {
+    /// time the auction starts in seconds since 01/01/1970
+    pub start: u64,
+    /// price of each token when the auction starts
+    pub start_price: Uint128,
+    /// lowest price of each token
+    pub end_price: Uint128,
+    /// amount the price drops every step
+    pub decay_step: Uint128,
+    /// number of seconds between price drops
+    pub step_interval: u64,
+}
+
+/// a recipient of a share of the mint proceeds
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct Payee {
+    /// address of the payee
+    pub address: HumanAddr,
+    /// payee's share of the proceeds in basis points
+    pub share: u16,
 }
 
 /// background count
@@ -178,6 +621,29 @@ pub struct BackgroundCount {
     pub count: u16,
 }
 
+/// a background that may be minted
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct BackgroundSupply {
+    /// name of the background variant
+    pub background: String,
+    /// optional max number of tokens that can be minted with this background
+    pub max_supply: Option<u16>,
+}
+
+/// availability of a background
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct BackgroundRemaining {
+    /// name of the background variant
+    pub background: String,
+    /// optional max number of tokens that can be minted with this background
+    pub max_supply: Option<u16>,
+    /// number of tokens minted with this background
+    pub minted: u16,
+    /// number of tokens that can still be minted with this background if it has a
+    /// max supply
+    pub remaining: Option<u16>,
+}
+
 /// the address and viewing key making an authenticated query request
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
 pub struct ViewerInfo {
@@ -8,8 +8,8 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ServerHandleMsg {
-    /// allow a minter to add genes to prevent future duplicates
-    AddGenes { genes: Vec<Vec<u8>> },
+    /// allow a minter to add genes to prevent future duplicates and count their traits
+    AddGenes { genes: Vec<GeneInfo> },
 }
 
 impl HandleCallback for ServerHandleMsg {
@@ -34,6 +34,10 @@ pub enum ServerQueryMsg {
         entropy: String,
         /// the names of the background layer variants to use
         backgrounds: Vec<String>,
+        /// optional address for each background used to randomize that gene instead of the
+        /// sender
+        #[serde(skip_serializing_if = "Option::is_none")]
+        senders: Option<Vec<HumanAddr>>,
     },
 }
 
@@ -0,0 +1,30 @@
+use crate::contract::BLOCK_SIZE;
+use cosmwasm_std::{HumanAddr, Uint128};
+use secret_toolkit::utils::HandleCallback;
+use serde::Serialize;
+
+/// snip20 handle msgs.
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Snip20HandleMsg {
+    /// register the code hash used for Receive callbacks
+    RegisterReceive {
+        /// code hash of the contract receiving tokens
+        code_hash: String,
+        /// optional message length padding
+        padding: Option<String>,
+    },
+    /// transfer tokens
+    Transfer {
+        /// address receiving the tokens
+        recipient: HumanAddr,
+        /// amount of tokens to transfer
+        amount: Uint128,
+        /// optional message length padding
+        padding: Option<String>,
+    },
+}
+
+impl HandleCallback for Snip20HandleMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
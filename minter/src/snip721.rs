@@ -22,6 +22,8 @@ impl HandleCallback for Snip721HandleMsg {
 /// token mint info used when doing a BatchMint
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Mint {
+    /// optional token id, if omitted, the nft contract uses its current token index
+    pub token_id: Option<String>,
     /// owner address
     pub owner: HumanAddr,
     /// optional public metadata that can be seen by everyone
@@ -1,8 +1,8 @@
-use cosmwasm_std::CanonicalAddr;
+use cosmwasm_std::{CanonicalAddr, Uint128};
 use serde::{Deserialize, Serialize};
 
 use crate::contract_info::StoreContractInfo;
-use crate::msg::BackgroundCount;
+use crate::msg::{BackgroundCount, BackgroundSupply, DutchAuction};
 
 /// storage key for the config
 pub const CONFIG_KEY: &[u8] = b"config";
@@ -10,6 +10,20 @@ pub const CONFIG_KEY: &[u8] = b"config";
 pub const MY_ADDRESS_KEY: &[u8] = b"myaddr";
 /// storage key for prng seed
 pub const PRNG_SEED_KEY: &[u8] = b"prngseed";
+/// storage key for the sale phases
+pub const PHASES_KEY: &[u8] = b"phases";
+/// storage key for the epoch that new commitments join
+pub const EPOCH_KEY: &[u8] = b"epoch";
+/// prefix for storage of whitelist allocations
+pub const PREFIX_WHITELIST: &[u8] = b"whitelist";
+/// prefix for storage of the number of tokens each address has minted
+pub const PREFIX_MINTED: &[u8] = b"minted";
+/// prefix for storage of paid mints that have not been claimed
+pub const PREFIX_COMMITS: &[u8] = b"commits";
+/// prefix for storage of the seeds that closed each epoch
+pub const PREFIX_EPOCH_SEEDS: &[u8] = b"epochseeds";
+/// prefix for storage of redeemed voucher IDs
+pub const PREFIX_USED_VOUCHERS: &[u8] = b"vouchers";
 /// prefix for storage of viewing keys
 pub const PREFIX_VIEW_KEY: &[u8] = b"viewkeys";
 /// prefix for the storage of revoked permits
@@ -24,14 +38,112 @@ pub struct Config {
     pub svg_contract: StoreContractInfo,
     /// true if minting should be halted
     pub halt: bool,
+    /// optional time minting starts in seconds since 01/01/1970
+    pub mint_start: Option<u64>,
+    /// optional time minting ends in seconds since 01/01/1970
+    pub mint_end: Option<u64>,
     /// multi sig address
     pub multi_sig: CanonicalAddr,
+    /// payees that split the mint proceeds.  If empty, all proceeds go to the multi sig
+    pub payees: Vec<StorePayee>,
     /// total number of tokens minted
     pub mint_cnt: u16,
+    /// price of each token
+    pub mint_price: Uint128,
+    /// denomination of the native coin used for payment
+    pub denom: String,
+    /// optional dutch auction price schedule that replaces the mint price
+    pub auction: Option<DutchAuction>,
+    /// max number of tokens that can be minted in one tx
+    pub max_per_tx: u16,
+    /// max number of tokens that can be minted
+    pub supply_cap: u16,
+    /// max number of tokens an address can mint over the life of the contract.  0 means
+    /// there is no limit
+    pub max_per_wallet: u16,
+    /// number of tokens (included in the supply cap) reserved for admin mints
+    pub reserve: u16,
+    /// number of tokens minted from the reserve
+    pub reserve_cnt: u16,
+    /// number of tokens airdropped
+    pub airdrop_cnt: u16,
+    /// number of tokens minted with vouchers
+    pub voucher_cnt: u16,
+    /// snip20 tokens accepted as payment and their prices
+    pub token_prices: Vec<StoreTokenPrice>,
     /// list of background counts
     pub backgd_cnts: Vec<BackgroundCount>,
+    /// backgrounds that may be minted.  If empty, any background may be minted
+    pub backgrounds: Vec<BackgroundSupply>,
     /// list of admins
     pub admins: Vec<CanonicalAddr>,
     /// viewing key used with the svg server and nft contracts
     pub viewing_key: String,
 }
+
+/// a paid mint that has not been claimed
+#[derive(Serialize, Deserialize)]
+pub struct Commitment {
+    /// serial numbers of the skulls
+    pub serials: Vec<u32>,
+    /// backgrounds of the skulls
+    pub backgrounds: Vec<String>,
+    /// block height of the mint
+    pub height: u64,
+    /// id of the epoch the mint was committed in
+    pub epoch: u64,
+    /// prng seed right after the mint was committed
+    pub seed: Vec<u8>,
+    /// true if claiming the skulls failed
+    pub failed: bool,
+}
+
+/// the epoch that new commitments join.  An epoch is closed by the first seed update in a
+/// later block, which provides entropy that did not exist when its mints were committed
+#[derive(Serialize, Deserialize)]
+pub struct Epoch {
+    /// epoch id
+    pub id: u64,
+    /// block height of the latest seed update
+    pub height: u64,
+    /// true if any mints were committed in this epoch
+    pub committed: bool,
+}
+
+/// the seed that closed an epoch
+#[derive(Serialize, Deserialize)]
+pub struct EpochSeed {
+    /// prng seed after the update that closed the epoch
+    pub seed: Vec<u8>,
+    /// block height the epoch was closed in
+    pub height: u64,
+    /// block time the epoch was closed in
+    pub time: u64,
+}
+
+/// a whitelisted address' allocation for a sale phase
+#[derive(Serialize, Deserialize)]
+pub struct WhitelistEntry {
+    /// number of skulls the address may mint during the phase
+    pub max_mints: u16,
+    /// number of skulls the address has already minted during the phase
+    pub used: u16,
+}
+
+/// a recipient of a share of the mint proceeds
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StorePayee {
+    /// address of the payee
+    pub address: CanonicalAddr,
+    /// payee's share of the proceeds in basis points
+    pub share: u16,
+}
+
+/// price of each skull when paying with a snip20 token
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StoreTokenPrice {
+    /// code hash and address of the snip20 contract
+    pub token: StoreContractInfo,
+    /// price of each skull in the token's smallest denomination
+    pub price: Uint128,
+}
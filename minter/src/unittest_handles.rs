@@ -0,0 +1,631 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::{handle, init};
+    use crate::contract::{AIRDROP_BATCH_SIZE, MAX_AIRDROP_SIZE};
+    use crate::contract_info::ContractInfo;
+    use crate::msg::{AirdropEntry, BackgroundSupply, HandleAnswer, HandleMsg, InitMsg};
+    use crate::rand::sha_256;
+    use crate::server_msgs::{GeneInfo, NewGenes, NewGenesResponse};
+    use crate::state::{Commitment, Config, CONFIG_KEY, PREFIX_COMMITS};
+    use crate::storage::{load, may_load, save};
+    use crate::voucher::{SignedVoucher, Voucher, VoucherParams, VoucherSignature};
+    use cosmwasm_std::testing::*;
+    use cosmwasm_std::{
+        from_binary, from_slice, to_binary, Binary, Coin, CosmosMsg, Empty, Env, Extern,
+        HandleResponse, HumanAddr, Querier, QuerierResult, QueryRequest, StdError, StdResult,
+        Uint128, WasmMsg, WasmQuery,
+    };
+    use cosmwasm_storage::ReadonlyPrefixedStorage;
+    use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+    use secret_toolkit::permit::pubkey_to_account;
+    use serde::Deserialize;
+    use std::any::Any;
+
+    // Helper functions
+
+    /// the parts of the svg server's NewGenes query the mock server uses
+    #[derive(Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum MockServerQuery {
+        NewGenes {
+            entropy: String,
+            backgrounds: Vec<String>,
+            senders: Option<Vec<HumanAddr>>,
+        },
+    }
+
+    /// the parts of a BatchMintNft message the tests check
+    #[derive(Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum MockNftMsg {
+        BatchMintNft { mints: Vec<MockMint> },
+    }
+
+    #[derive(Deserialize)]
+    struct MockMint {
+        token_id: Option<String>,
+        owner: HumanAddr,
+    }
+
+    /// the svg server's AddGenes message
+    #[derive(Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum MockServerMsg {
+        AddGenes { genes: Vec<GeneInfo> },
+    }
+
+    /// mock svg server that returns a distinct gene for each requested background, or an
+    /// error if it is set to fail.  A gene's genetic image is the address of its sender if
+    /// senders were given
+    pub struct MockQuerier {
+        pub fail: bool,
+    }
+
+    impl Querier for MockQuerier {
+        fn raw_query(&self, request: &[u8]) -> QuerierResult {
+            if self.fail {
+                return Ok(Err(StdError::generic_err("Server failed to roll genes")));
+            }
+            let request: QueryRequest<Empty> = from_slice(request).unwrap();
+            let msg = match request {
+                QueryRequest::Wasm(WasmQuery::Smart { msg, .. }) => msg,
+                _ => panic!("Unexpected query"),
+            };
+            let MockServerQuery::NewGenes {
+                entropy,
+                backgrounds,
+                senders,
+            } = from_binary(&msg).unwrap();
+            let genes = (0..backgrounds.len())
+                .map(|idx| {
+                    let mut unique_check = entropy.as_bytes().to_vec();
+                    unique_check.extend_from_slice(&(idx as u32).to_le_bytes());
+                    let genetic_image = senders
+                        .as_ref()
+                        .map(|s| s[idx].as_str().as_bytes().to_vec())
+                        .unwrap_or_else(|| vec![idx as u8]);
+                    GeneInfo {
+                        current_image: vec![idx as u8],
+                        genetic_image,
+                        unique_check,
+                    }
+                })
+                .collect();
+            Ok(to_binary(&NewGenesResponse {
+                new_genes: NewGenes { genes },
+            }))
+        }
+    }
+
+    fn init_helper(fail: bool) -> Extern<MockStorage, MockApi, MockQuerier> {
+        let mut deps = Extern {
+            storage: MockStorage::default(),
+            api: MockApi::new(20),
+            querier: MockQuerier { fail },
+        };
+        let env = mock_env("admin", &[]);
+
+        let init_msg = InitMsg {
+            nft_contract: ContractInfo {
+                code_hash: "nft hash".to_string(),
+                address: HumanAddr("nft".to_string()),
+            },
+            svg_server: ContractInfo {
+                code_hash: "svg hash".to_string(),
+                address: HumanAddr("svg".to_string()),
+            },
+            multi_sig: HumanAddr("multisig".to_string()),
+            mint_price: Uint128(1000),
+            denom: "uscrt".to_string(),
+            max_per_tx: 5,
+            supply_cap: 200,
+            max_per_wallet: 0,
+            reserve: 0,
+            entropy: "We're going to need a bigger boat".to_string(),
+        };
+        init(&mut deps, env, init_msg).unwrap();
+
+        let handle_msg = HandleMsg::SetBackgrounds {
+            backgrounds: vec![
+                BackgroundSupply {
+                    background: "Cyan".to_string(),
+                    max_supply: None,
+                },
+                BackgroundSupply {
+                    background: "Red".to_string(),
+                    max_supply: None,
+                },
+            ],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_msg = HandleMsg::SetMintStatus { halt: false };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        deps
+    }
+
+    fn env_at(sender: &str, height: u64, funds: &[Coin]) -> Env {
+        let mut env = mock_env(sender, funds);
+        env.block.height = height;
+        env
+    }
+
+    fn extract_error_msg<T: Any>(error: StdResult<T>) -> String {
+        match error {
+            Ok(_response) => panic!("Expected error, but had Ok response"),
+            Err(err) => match err {
+                StdError::GenericErr { msg, .. } => msg,
+                _ => panic!("Unexpected error result {:?}", err),
+            },
+        }
+    }
+
+    fn load_commits(
+        deps: &Extern<MockStorage, MockApi, MockQuerier>,
+        owner: &str,
+    ) -> Vec<Commitment> {
+        let owner_raw = deps
+            .api
+            .canonical_address(&HumanAddr(owner.to_string()))
+            .unwrap();
+        let commit_store = ReadonlyPrefixedStorage::new(PREFIX_COMMITS, &deps.storage);
+        may_load(&commit_store, owner_raw.as_slice())
+            .unwrap()
+            .unwrap_or_else(Vec::new)
+    }
+
+    fn msg_contracts(response: &HandleResponse) -> Vec<HumanAddr> {
+        response
+            .messages
+            .iter()
+            .map(|m| match m {
+                CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => contract_addr.clone(),
+                _ => panic!("Unexpected message"),
+            })
+            .collect()
+    }
+
+    fn msg_binaries(response: &HandleResponse) -> Vec<Binary> {
+        response
+            .messages
+            .iter()
+            .map(|m| match m {
+                CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => msg.clone(),
+                _ => panic!("Unexpected message"),
+            })
+            .collect()
+    }
+
+    fn drops(qty: usize) -> Vec<AirdropEntry> {
+        (0..qty)
+            .map(|i| AirdropEntry {
+                owner: HumanAddr(format!("holder{}", i)),
+                background: if i % 2 == 0 { "Cyan" } else { "Red" }.to_string(),
+            })
+            .collect()
+    }
+
+    fn claim(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        sender: &str,
+        height: u64,
+    ) -> (HandleResponse, Vec<String>) {
+        let response = handle(deps, env_at(sender, height, &[]), HandleMsg::Claim {}).unwrap();
+        let answer: HandleAnswer = from_binary(response.data.as_ref().unwrap()).unwrap();
+        let token_ids = match answer {
+            HandleAnswer::Claim { token_ids, .. } => token_ids,
+            _ => panic!("Unexpected claim answer"),
+        };
+        (response, token_ids)
+    }
+
+    fn paid_mint(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        sender: &str,
+        height: u64,
+        backgrounds: &[&str],
+    ) {
+        let funds = vec![Coin {
+            denom: "uscrt".to_string(),
+            amount: Uint128(1000 * backgrounds.len() as u128),
+        }];
+        let handle_msg = HandleMsg::Mint {
+            backgrounds: backgrounds.iter().map(|b| b.to_string()).collect(),
+            entropy: "mint entropy".to_string(),
+        };
+        handle(deps, env_at(sender, height, &funds), handle_msg).unwrap();
+    }
+
+    fn pub_key(key: u8) -> Binary {
+        let secp = Secp256k1::signing_only();
+        let secret = SecretKey::from_slice(&[key; 32]).unwrap();
+        Binary(
+            PublicKey::from_secret_key(&secp, &secret)
+                .serialize()
+                .to_vec(),
+        )
+    }
+
+    fn add_voucher_signer(deps: &mut Extern<MockStorage, MockApi, MockQuerier>, key: u8) {
+        let mut config: Config = load(&deps.storage, CONFIG_KEY).unwrap();
+        config.admins.push(pubkey_to_account(&pub_key(key)));
+        save(&mut deps.storage, CONFIG_KEY, &config).unwrap();
+    }
+
+    fn voucher_params(voucher_id: &str, expires: u64) -> VoucherParams {
+        let env = mock_env("alice", &[]);
+        VoucherParams {
+            expires,
+            minter: env.contract.address,
+            quantity: 2,
+            recipient: HumanAddr("alice".to_string()),
+            voucher_id: voucher_id.to_string(),
+        }
+    }
+
+    fn sign_voucher(key: u8, params: VoucherParams) -> Voucher {
+        let env = mock_env("alice", &[]);
+        let secp = Secp256k1::signing_only();
+        let secret = SecretKey::from_slice(&[key; 32]).unwrap();
+        let signed_bytes = to_binary(&SignedVoucher::new(&params, &env.block.chain_id)).unwrap();
+        let signed_hash = sha_256(signed_bytes.as_slice());
+        let signature = secp.sign(&Message::from_slice(&signed_hash).unwrap(), &secret);
+        Voucher {
+            params,
+            signature: VoucherSignature {
+                pub_key: pub_key(key),
+                signature: Binary(signature.serialize_compact().to_vec()),
+            },
+        }
+    }
+
+    fn redeem(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        voucher: Voucher,
+    ) -> StdResult<HandleResponse> {
+        let handle_msg = HandleMsg::RedeemVoucher {
+            voucher,
+            backgrounds: vec!["Cyan".to_string(), "Red".to_string()],
+            entropy: "voucher entropy".to_string(),
+        };
+        handle(deps, mock_env("alice", &[]), handle_msg)
+    }
+
+    // Claim tests
+
+    #[test]
+    fn test_claim_deferred_until_epoch_closed_in_earlier_block() {
+        let mut deps = init_helper(false);
+        let height = mock_env("admin", &[]).block.height;
+
+        paid_mint(&mut deps, "alice", height + 1, &["Cyan", "Red"]);
+
+        // nothing can be claimed in the block of the mint
+        let (response, token_ids) = claim(&mut deps, "alice", height + 1);
+        assert!(response.messages.is_empty());
+        assert!(token_ids.is_empty());
+        assert_eq!(load_commits(&deps, "alice").len(), 1);
+
+        // this claim closes the epoch, but the genes must wait for the next block
+        let (response, token_ids) = claim(&mut deps, "alice", height + 2);
+        assert!(response.messages.is_empty());
+        assert!(token_ids.is_empty());
+        let commits = load_commits(&deps, "alice");
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].serials, vec![1, 2]);
+        assert!(!commits[0].failed);
+
+        // someone else's claim can not mint alice's skulls
+        let (response, token_ids) = claim(&mut deps, "bob", height + 3);
+        assert!(response.messages.is_empty());
+        assert!(token_ids.is_empty());
+        assert_eq!(load_commits(&deps, "alice").len(), 1);
+    }
+
+    #[test]
+    fn test_claim_ready_commitments() {
+        let mut deps = init_helper(false);
+        let height = mock_env("admin", &[]).block.height;
+
+        paid_mint(&mut deps, "alice", height + 1, &["Cyan", "Red"]);
+        // a later mint in the closing block joins the next epoch
+        paid_mint(&mut deps, "alice", height + 2, &["Red"]);
+
+        // only the commitment whose epoch closed in an earlier block is claimed
+        let (response, token_ids) = claim(&mut deps, "alice", height + 3);
+        assert_eq!(token_ids, vec!["1".to_string(), "2".to_string()]);
+        assert_eq!(
+            msg_contracts(&response),
+            vec![HumanAddr("nft".to_string()), HumanAddr("svg".to_string())]
+        );
+        let commits = load_commits(&deps, "alice");
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].serials, vec![3]);
+
+        // the claim above closed the second epoch, so it is ready in the next block
+        let (response, token_ids) = claim(&mut deps, "alice", height + 4);
+        assert_eq!(token_ids, vec!["3".to_string()]);
+        assert_eq!(response.messages.len(), 2);
+        assert!(load_commits(&deps, "alice").is_empty());
+
+        // claiming again does nothing
+        let (response, token_ids) = claim(&mut deps, "alice", height + 5);
+        assert!(response.messages.is_empty());
+        assert!(token_ids.is_empty());
+    }
+
+    #[test]
+    fn test_claim_sets_aside_failed_commitment_until_recovered() {
+        let mut deps = init_helper(true);
+        let height = mock_env("admin", &[]).block.height;
+
+        paid_mint(&mut deps, "alice", height + 1, &["Cyan", "Red"]);
+        let (_, token_ids) = claim(&mut deps, "alice", height + 2);
+        assert!(token_ids.is_empty());
+
+        // the server rejects the genes, so the commitment is set aside
+        let (response, token_ids) = claim(&mut deps, "alice", height + 3);
+        assert!(response.messages.is_empty());
+        assert!(token_ids.is_empty());
+        assert!(response
+            .log
+            .iter()
+            .any(|l| l.key == "failed" && l.value == "1"));
+        let commits = load_commits(&deps, "alice");
+        assert_eq!(commits.len(), 1);
+        assert!(commits[0].failed);
+
+        // a failed commitment is not retried, even once the server works again
+        deps.querier.fail = false;
+        let (response, token_ids) = claim(&mut deps, "alice", height + 4);
+        assert!(response.messages.is_empty());
+        assert!(token_ids.is_empty());
+
+        // only an admin can recover it
+        let handle_msg = HandleMsg::RecoverMint {
+            owner: HumanAddr("alice".to_string()),
+            token_id: "2".to_string(),
+            backgrounds: Some(vec!["Red".to_string(), "Red".to_string()]),
+        };
+        let result = handle(&mut deps, env_at("alice", height + 5, &[]), handle_msg);
+        assert!(matches!(result, Err(StdError::Unauthorized { .. })));
+
+        // recovery must replace every background
+        let handle_msg = HandleMsg::RecoverMint {
+            owner: HumanAddr("alice".to_string()),
+            token_id: "2".to_string(),
+            backgrounds: Some(vec!["Red".to_string()]),
+        };
+        let error = extract_error_msg(handle(
+            &mut deps,
+            env_at("admin", height + 5, &[]),
+            handle_msg,
+        ));
+        assert!(error.contains("Must provide 2 replacement backgrounds"));
+
+        let handle_msg = HandleMsg::RecoverMint {
+            owner: HumanAddr("alice".to_string()),
+            token_id: "2".to_string(),
+            backgrounds: Some(vec!["Red".to_string(), "Red".to_string()]),
+        };
+        handle(&mut deps, env_at("admin", height + 5, &[]), handle_msg).unwrap();
+        let commits = load_commits(&deps, "alice");
+        assert!(!commits[0].failed);
+        assert_eq!(
+            commits[0].backgrounds,
+            vec!["Red".to_string(), "Red".to_string()]
+        );
+        let config: Config = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert!(config.backgd_cnts.iter().all(|b| b.background != "Cyan"));
+        assert_eq!(
+            config
+                .backgd_cnts
+                .iter()
+                .find(|b| b.background == "Red")
+                .map(|b| b.count),
+            Some(2)
+        );
+
+        // a recovered commitment can not be recovered again until it fails
+        let handle_msg = HandleMsg::RecoverMint {
+            owner: HumanAddr("alice".to_string()),
+            token_id: "1".to_string(),
+            backgrounds: None,
+        };
+        let error = extract_error_msg(handle(
+            &mut deps,
+            env_at("admin", height + 5, &[]),
+            handle_msg,
+        ));
+        assert!(error.contains("The mint of token 1 has not failed"));
+
+        let (response, token_ids) = claim(&mut deps, "alice", height + 6);
+        assert_eq!(token_ids, vec!["1".to_string(), "2".to_string()]);
+        assert_eq!(response.messages.len(), 2);
+        assert!(load_commits(&deps, "alice").is_empty());
+    }
+    // Voucher tests
+
+    #[test]
+    fn test_redeem_voucher_rejects_replay() {
+        let mut deps = init_helper(false);
+        add_voucher_signer(&mut deps, 1);
+        let time = mock_env("alice", &[]).block.time;
+        let voucher = sign_voucher(1, voucher_params("v1", time + 100));
+
+        let response = redeem(&mut deps, voucher.clone()).unwrap();
+        let answer: HandleAnswer = from_binary(response.data.as_ref().unwrap()).unwrap();
+        match answer {
+            HandleAnswer::RedeemVoucher {
+                skulls_minted,
+                token_ids,
+            } => {
+                assert_eq!(skulls_minted, 2);
+                assert_eq!(token_ids, vec!["1".to_string(), "2".to_string()]);
+            }
+            _ => panic!("Unexpected redeem answer"),
+        }
+
+        let error = extract_error_msg(redeem(&mut deps, voucher));
+        assert!(error.contains("Voucher v1 has already been redeemed"));
+        let commits = load_commits(&deps, "alice");
+        assert_eq!(commits.len(), 1);
+        let config: Config = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(config.voucher_cnt, 2);
+        assert_eq!(config.mint_cnt, 2);
+
+        // a new voucher id can still be redeemed
+        let voucher = sign_voucher(1, voucher_params("v2", time + 100));
+        assert!(redeem(&mut deps, voucher).is_ok());
+    }
+
+    #[test]
+    fn test_redeem_voucher_rejects_expired() {
+        let mut deps = init_helper(false);
+        add_voucher_signer(&mut deps, 1);
+        let time = mock_env("alice", &[]).block.time;
+
+        let voucher = sign_voucher(1, voucher_params("v1", time));
+        let error = extract_error_msg(redeem(&mut deps, voucher));
+        assert!(error.contains("Voucher has expired"));
+
+        let voucher = sign_voucher(1, voucher_params("v2", time - 1));
+        let error = extract_error_msg(redeem(&mut deps, voucher));
+        assert!(error.contains("Voucher has expired"));
+        assert!(load_commits(&deps, "alice").is_empty());
+
+        // an expired voucher is not marked as used
+        let voucher = sign_voucher(1, voucher_params("v1", time + 1));
+        assert!(redeem(&mut deps, voucher).is_ok());
+    }
+
+    #[test]
+    fn test_redeem_voucher_rejects_wrong_signature() {
+        let mut deps = init_helper(false);
+        add_voucher_signer(&mut deps, 1);
+        let time = mock_env("alice", &[]).block.time;
+
+        // signed by someone who is not an admin
+        let voucher = sign_voucher(2, voucher_params("v1", time + 100));
+        let error = extract_error_msg(redeem(&mut deps, voucher));
+        assert!(error.contains("Voucher was not signed by an admin"));
+
+        // an admin's signature of different params
+        let mut voucher = sign_voucher(1, voucher_params("v1", time + 100));
+        voucher.params.voucher_id = "v2".to_string();
+        let error = extract_error_msg(redeem(&mut deps, voucher));
+        assert!(error.contains("Failed to verify the voucher signature"));
+
+        // an admin's signature presented with someone else's public key
+        let mut voucher = sign_voucher(1, voucher_params("v1", time + 100));
+        voucher.signature.pub_key = pub_key(2);
+        let error = extract_error_msg(redeem(&mut deps, voucher));
+        assert!(error.contains("Failed to verify the voucher signature"));
+
+        // a voucher for a different recipient
+        let mut params = voucher_params("v1", time + 100);
+        params.recipient = HumanAddr("bob".to_string());
+        let voucher = sign_voucher(1, params);
+        let error = extract_error_msg(redeem(&mut deps, voucher));
+        assert!(error.contains("Voucher can only be redeemed by its recipient"));
+        assert!(load_commits(&deps, "alice").is_empty());
+
+        // the admin's signature of the original params is still accepted
+        let voucher = sign_voucher(1, voucher_params("v1", time + 100));
+        assert!(redeem(&mut deps, voucher).is_ok());
+    }
+    // Airdrop tests
+
+    #[test]
+    fn test_airdrop_chunks_mints() {
+        let mut deps = init_helper(false);
+        let qty = AIRDROP_BATCH_SIZE + 10;
+
+        let handle_msg = HandleMsg::Airdrop { drops: drops(qty) };
+        let response = handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let answer: HandleAnswer = from_binary(response.data.as_ref().unwrap()).unwrap();
+        match answer {
+            HandleAnswer::Airdrop {
+                skulls_minted,
+                token_ids,
+            } => {
+                assert_eq!(skulls_minted as usize, qty);
+                let expected: Vec<String> = (1..=qty).map(|i| i.to_string()).collect();
+                assert_eq!(token_ids, expected);
+            }
+            _ => panic!("Unexpected airdrop answer"),
+        }
+
+        // two batches of mints followed by one message recording every gene
+        assert_eq!(
+            msg_contracts(&response),
+            vec![
+                HumanAddr("nft".to_string()),
+                HumanAddr("nft".to_string()),
+                HumanAddr("svg".to_string())
+            ]
+        );
+        let binaries = msg_binaries(&response);
+        let mut minted: Vec<MockMint> = Vec::new();
+        for (idx, binary) in binaries.iter().take(2).enumerate() {
+            let MockNftMsg::BatchMintNft { mints } = from_binary(binary).unwrap();
+            let expected_len = if idx == 0 { AIRDROP_BATCH_SIZE } else { 10 };
+            assert_eq!(mints.len(), expected_len);
+            minted.extend(mints);
+        }
+        for (i, mint) in minted.iter().enumerate() {
+            assert_eq!(mint.token_id, Some((i + 1).to_string()));
+            assert_eq!(mint.owner, HumanAddr(format!("holder{}", i)));
+        }
+        // each gene was rolled with its recipient as the sender
+        let MockServerMsg::AddGenes { genes } = from_binary(&binaries[2]).unwrap();
+        assert_eq!(genes.len(), qty);
+        for (i, gene) in genes.iter().enumerate() {
+            assert_eq!(gene.genetic_image, format!("holder{}", i).into_bytes());
+        }
+
+        let config: Config = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(config.airdrop_cnt as usize, qty);
+        assert_eq!(config.mint_cnt as usize, qty);
+
+        // a drop that fits in one batch sends a single mint message
+        let handle_msg = HandleMsg::Airdrop {
+            drops: drops(AIRDROP_BATCH_SIZE),
+        };
+        let response = handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        assert_eq!(
+            msg_contracts(&response),
+            vec![HumanAddr("nft".to_string()), HumanAddr("svg".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_airdrop_size_limits() {
+        let mut deps = init_helper(false);
+
+        let handle_msg = HandleMsg::Airdrop { drops: drops(1) };
+        let result = handle(&mut deps, mock_env("alice", &[]), handle_msg);
+        assert!(matches!(result, Err(StdError::Unauthorized { .. })));
+
+        let handle_msg = HandleMsg::Airdrop { drops: Vec::new() };
+        let error = extract_error_msg(handle(&mut deps, mock_env("admin", &[]), handle_msg));
+        assert!(error.contains("The airdrop list is empty"));
+
+        let handle_msg = HandleMsg::Airdrop {
+            drops: drops(MAX_AIRDROP_SIZE + 1),
+        };
+        let error = extract_error_msg(handle(&mut deps, mock_env("admin", &[]), handle_msg));
+        assert!(error.contains(&format!(
+            "Only {} Mystic Skulls may be airdropped at once",
+            MAX_AIRDROP_SIZE
+        )));
+
+        let config: Config = load(&deps.storage, CONFIG_KEY).unwrap();
+        assert_eq!(config.airdrop_cnt, 0);
+        assert_eq!(config.mint_cnt, 0);
+
+        let handle_msg = HandleMsg::Airdrop {
+            drops: drops(MAX_AIRDROP_SIZE),
+        };
+        assert!(handle(&mut deps, mock_env("admin", &[]), handle_msg).is_ok());
+    }
+}
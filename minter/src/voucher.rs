@@ -0,0 +1,113 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{
+    to_binary, Api, Binary, CanonicalAddr, Coin, HumanAddr, StdError, StdResult, Uint128,
+};
+use secret_toolkit::permit::pubkey_to_account;
+
+use crate::rand::sha_256;
+
+/// a free mint voucher signed by an admin
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct Voucher {
+    /// the signed voucher parameters
+    pub params: VoucherParams,
+    /// the admin's signature of the voucher
+    pub signature: VoucherSignature,
+}
+
+/// the parameters of a voucher.  Fields are in alphabetical order so they serialize the
+/// same way as the amino json the admin's wallet signs
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct VoucherParams {
+    /// time the voucher expires in seconds since 01/01/1970
+    pub expires: u64,
+    /// address of the minter the voucher can be redeemed with
+    pub minter: HumanAddr,
+    /// number of skulls the voucher can mint
+    pub quantity: u16,
+    /// address that can redeem the voucher
+    pub recipient: HumanAddr,
+    /// ID used to prevent the voucher from being redeemed more than once
+    pub voucher_id: String,
+}
+
+/// a secp256k1 signature and the public key that created it
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct VoucherSignature {
+    /// compressed secp256k1 public key of the signer
+    pub pub_key: Binary,
+    /// signature of the voucher's sign doc
+    pub signature: Binary,
+}
+
+impl Voucher {
+    /// Returns StdResult<CanonicalAddr> of the address that signed the voucher
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - a reference to the Api used to verify the signature
+    /// * `chain_id` - the id of the chain the voucher was signed for
+    pub fn signer<A: Api>(&self, api: &A, chain_id: &str) -> StdResult<CanonicalAddr> {
+        let signed_bytes = to_binary(&SignedVoucher::new(&self.params, chain_id))?;
+        let signed_hash = sha_256(signed_bytes.as_slice());
+        let verified = api
+            .secp256k1_verify(
+                &signed_hash,
+                self.signature.signature.as_slice(),
+                self.signature.pub_key.as_slice(),
+            )
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
+        if !verified {
+            return Err(StdError::generic_err(
+                "Failed to verify the voucher signature",
+            ));
+        }
+        Ok(pubkey_to_account(&self.signature.pub_key))
+    }
+}
+
+/// the amino json sign doc an admin signs to create a voucher
+#[derive(Serialize)]
+pub(crate) struct SignedVoucher<'a> {
+    account_number: Uint128,
+    chain_id: &'a str,
+    fee: Fee,
+    memo: String,
+    msgs: Vec<VoucherMsg<'a>>,
+    sequence: Uint128,
+}
+
+impl<'a> SignedVoucher<'a> {
+    pub(crate) fn new(params: &'a VoucherParams, chain_id: &'a str) -> Self {
+        Self {
+            account_number: Uint128(0),
+            chain_id,
+            fee: Fee {
+                amount: vec![],
+                gas: Uint128(1),
+            },
+            memo: String::new(),
+            msgs: vec![VoucherMsg {
+                r#type: "mint_voucher",
+                value: params,
+            }],
+            sequence: Uint128(0),
+        }
+    }
+}
+
+/// the fee of a sign doc
+#[derive(Serialize)]
+struct Fee {
+    amount: Vec<Coin>,
+    gas: Uint128,
+}
+
+/// the message of a voucher sign doc
+#[derive(Serialize)]
+struct VoucherMsg<'a> {
+    r#type: &'a str,
+    value: &'a VoucherParams,
+}
@@ -13,21 +13,28 @@ use secret_toolkit::{
 
 use crate::metadata::{Metadata, Trait};
 use crate::msg::{
-    CategoryInfo, CommonMetadata, Dependencies, ForcedVariants, GeneInfo, HandleAnswer, HandleMsg,
-    InitMsg, LayerId, QueryAnswer, QueryMsg, StoredDependencies, StoredLayerId, VariantInfo,
-    VariantInfoPlus, VariantModInfo, ViewerInfo, Weights,
+    roll_order, rolled_before, Archetype, CategoryHistogram, CategoryInfo, CommonMetadata,
+    Conditional, Dependencies, ForcedVariants, Fragment, GeneInfo, HandleAnswer, HandleMsg,
+    InitMsg, LayerId, Palette, QueryAnswer, QueryMsg, Recolor, StoredArchetype, StoredColorSwap,
+    StoredConditional, StoredDependencies, StoredLayerId, StoredRecolor, SvgCanvas, VariantCount,
+    VariantInfo, VariantInfoPlus, VariantModInfo, ViewerInfo, Weights,
 };
 use crate::rand::{extend_entropy, sha_256, Prng};
+use crate::render::{expand_fragments, references, variant_svg};
 use crate::state::{
-    Category, RollConfig, Variant, ADMINS_KEY, DEPENDENCIES_KEY, HIDERS_KEY, METADATA_KEY,
-    MINTERS_KEY, MY_ADDRESS_KEY, PREFIX_CATEGORY, PREFIX_CATEGORY_MAP, PREFIX_GENE,
-    PREFIX_REVOKED_PERMITS, PREFIX_VARIANT, PREFIX_VARIANT_MAP, PREFIX_VIEW_KEY, PRNG_SEED_KEY,
-    ROLL_CONF_KEY, VIEWERS_KEY,
+    Category, MintedTraits, RollConfig, Variant, ADMINS_KEY, CANVAS_KEY, DEPENDENCIES_KEY,
+    EXCLUSIONS_KEY, HIDERS_KEY, METADATA_KEY, MINTED_TRAITS_KEY, MINTERS_KEY, MY_ADDRESS_KEY,
+    PALETTES_KEY, PREFIX_CATEGORY, PREFIX_CATEGORY_MAP, PREFIX_FRAGMENT, PREFIX_FRAGMENT_USERS,
+    PREFIX_GENE, PREFIX_PALETTE_USERS, PREFIX_REVOKED_PERMITS, PREFIX_VARIANT, PREFIX_VARIANT_MAP,
+    PREFIX_VIEW_KEY, PRNG_SEED_KEY, ROLL_CONF_KEY, VIEWERS_KEY,
 };
 use crate::storage::{load, may_load, remove, save};
+use crate::validation::{check_config, validate_config, Scope};
 use crate::viewing_key::{ViewingKey, VIEWING_KEY_SIZE};
 
 pub const BLOCK_SIZE: usize = 256;
+/// number of times rolling a single gene may start over before giving up
+pub const MAX_RESTARTS: u32 = 100;
 
 ////////////////////////////////////// Init ///////////////////////////////////////
 /// Returns InitResult
@@ -58,6 +65,8 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         cat_cnt: 0u8,
         skip: Vec::new(),
         jaw_weights: vec![msg.jaw_weight, msg.jawless_weight],
+        archetype: None,
+        render_order: Vec::new(),
     };
     save(&mut deps.storage, ROLL_CONF_KEY, &roll)?;
 
@@ -84,7 +93,18 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             skip,
             jaw_weight,
             jawless_weight,
-        } => try_set_roll_config(deps, &env.message.sender, skip, jaw_weight, jawless_weight),
+            archetype,
+        } => try_set_roll_config(
+            deps,
+            &env.message.sender,
+            skip,
+            jaw_weight,
+            jawless_weight,
+            archetype,
+        ),
+        HandleMsg::SetRenderOrder { categories } => {
+            try_set_render_order(deps, &env.message.sender, &categories)
+        }
         HandleMsg::AddCategories { categories } => {
             try_add_categories(deps, &env.message.sender, categories)
         }
@@ -96,6 +116,7 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             new_name,
             forced_variants,
             weights,
+            conditionals,
         } => try_modify_category(
             deps,
             &env.message.sender,
@@ -103,6 +124,7 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             new_name,
             forced_variants,
             weights,
+            conditionals,
         ),
         HandleMsg::ModifyVariants { modifications } => {
             try_modify_variants(deps, &env.message.sender, modifications)
@@ -111,7 +133,24 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             public_metadata,
             private_metadata,
         } => try_set_metadata(deps, &env.message.sender, public_metadata, private_metadata),
+        HandleMsg::SetCanvas { canvas } => try_set_canvas(deps, &env.message.sender, canvas),
+        HandleMsg::SetFragments { fragments } => {
+            try_set_fragments(deps, &env.message.sender, fragments)
+        }
+        HandleMsg::RemoveFragments { names } => {
+            try_remove_fragments(deps, &env.message.sender, &names)
+        }
+        HandleMsg::SetPalettes { palettes } => {
+            try_set_palettes(deps, &env.message.sender, palettes)
+        }
+        HandleMsg::RemovePalettes { names } => {
+            try_remove_palettes(deps, &env.message.sender, &names)
+        }
         HandleMsg::AddGenes { genes } => try_add_gene(deps, &env.message.sender, genes),
+        HandleMsg::BackfillTraitCounts {
+            genetic_images,
+            complete,
+        } => try_backfill_trait_counts(deps, &env.message.sender, &genetic_images, complete),
         HandleMsg::AddAdmins { admins } => {
             try_process_auth_list(deps, &env.message.sender, &admins, true, AddrType::Admin)
         }
@@ -156,6 +195,12 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         HandleMsg::ModifyHiders { hiders } => {
             try_process_dep_list(deps, &env.message.sender, &hiders, Action::Modify, false)
         }
+        HandleMsg::AddExclusions { exclusions } => {
+            try_process_exclusions(deps, &env.message.sender, &exclusions, true)
+        }
+        HandleMsg::RemoveExclusions { exclusions } => {
+            try_process_exclusions(deps, &env.message.sender, &exclusions, false)
+        }
         HandleMsg::RevokePermit { permit_name } => {
             revoke_permit(&mut deps.storage, &env.message.sender, &permit_name)
         }
@@ -165,17 +210,17 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
 
 /// Returns HandleResult
 ///
-/// adds a gene to avoid future duplication
+/// adds a gene to avoid future duplication and counts its traits
 ///
 /// # Arguments
 ///
 /// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
 /// * `sender` - a reference to the message sender
-/// * `genes` - image index arrays of recently minted NFTs
+/// * `genes` - gene infos of recently minted NFTs
 fn try_add_gene<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     sender: &HumanAddr,
-    genes: Vec<Vec<u8>>,
+    genes: Vec<GeneInfo>,
 ) -> HandleResult {
     // only allow minters to do this
     let minters: Vec<CanonicalAddr> =
@@ -184,17 +229,84 @@ fn try_add_gene<S: Storage, A: Api, Q: Querier>(
     if !minters.contains(&sender_raw) {
         return Err(StdError::unauthorized());
     }
+    let mut minted: MintedTraits =
+        may_load(&deps.storage, MINTED_TRAITS_KEY)?.unwrap_or(MintedTraits {
+            total: 0,
+            counts: Vec::new(),
+            backfilled: false,
+        });
     let mut gene_store = PrefixedStorage::new(PREFIX_GENE, &mut deps.storage);
     // can not allow a duplicate, even though this should have been weeded out before this msg
     for gene in genes.into_iter() {
-        if may_load::<bool, _>(&gene_store, &gene)?.is_some() {
+        if may_load::<bool, _>(&gene_store, &gene.unique_check)?.is_some() {
             return Err(StdError::generic_err("Found a genetic twin"));
         }
-        save(&mut gene_store, &gene, &true)?;
+        save(&mut gene_store, &gene.unique_check, &true)?;
+        tally_traits(&mut minted.counts, &gene.genetic_image);
+        minted.total += 1;
     }
+    save(&mut deps.storage, MINTED_TRAITS_KEY, &minted)?;
     Ok(HandleResponse::default())
 }
 
+/// Returns HandleResult
+///
+/// counts the traits of genes minted before the trait counts were kept
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `genetic_images` - genetic images of previously minted genes
+/// * `complete` - true if these are the last of the previously minted genes
+fn try_backfill_trait_counts<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    genetic_images: &[Vec<u8>],
+    complete: bool,
+) -> HandleResult {
+    // only allow admins to do this
+    let admins: Vec<CanonicalAddr> = load(&deps.storage, ADMINS_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    let mut minted: MintedTraits =
+        may_load(&deps.storage, MINTED_TRAITS_KEY)?.unwrap_or(MintedTraits {
+            total: 0,
+            counts: Vec::new(),
+            backfilled: false,
+        });
+    // counting a gene twice would skew every score
+    if minted.backfilled {
+        return Err(StdError::generic_err(
+            "The trait counts have already been backfilled",
+        ));
+    }
+    let roll: RollConfig = load(&deps.storage, ROLL_CONF_KEY)?;
+    for image in genetic_images.iter() {
+        if image.len() != roll.cat_cnt as usize {
+            return Err(StdError::generic_err(format!(
+                "Genetic images must have {} layers",
+                roll.cat_cnt
+            )));
+        }
+        tally_traits(&mut minted.counts, image);
+        minted.total += 1;
+    }
+    minted.backfilled = complete;
+    save(&mut deps.storage, MINTED_TRAITS_KEY, &minted)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::BackfillTraitCounts {
+            total: minted.total,
+            complete,
+        })?),
+    })
+}
+
 /// Returns HandleResult
 ///
 /// sets layer categories to skip when rolling and the ones to roll first
@@ -206,12 +318,14 @@ fn try_add_gene<S: Storage, A: Api, Q: Querier>(
 /// * `skip` - optional list of categories to skip when rolling
 /// * `jaw_weight` - optional weight of jawed skulls
 /// * `jawless_weight` - optional weight of jawless skulls
+/// * `archetype` - optional archetype categories and variants
 fn try_set_roll_config<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     sender: &HumanAddr,
     skip: Option<Vec<String>>,
     jaw_weight: Option<u16>,
     jawless_weight: Option<u16>,
+    archetype: Option<Archetype>,
 ) -> HandleResult {
     // only allow admins to do this
     let admins: Vec<CanonicalAddr> = load(&deps.storage, ADMINS_KEY)?;
@@ -252,10 +366,20 @@ fn try_set_roll_config<S: Storage, A: Api, Q: Querier>(
             save_it = true;
         }
     }
+    // if setting the archetype
+    if let Some(arch) = archetype {
+        let stored = Some(arch.to_stored(&deps.storage)?);
+        if roll.archetype != stored {
+            roll.archetype = stored;
+            save_it = true;
+        }
+    }
     if save_it {
         save(&mut deps.storage, ROLL_CONF_KEY, &roll)?;
     }
 
+    // make sure the changes leave a consistent layer configuration
+    check_config(&deps.storage, &Scope::Roll)?;
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
@@ -265,6 +389,248 @@ fn try_set_roll_config<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Returns HandleResult
+///
+/// sets the svg canvas that wraps every image
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `canvas` - the new svg canvas
+fn try_set_canvas<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    canvas: SvgCanvas,
+) -> HandleResult {
+    // only allow admins to do this
+    let admins: Vec<CanonicalAddr> = load(&deps.storage, ADMINS_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    canvas.validate()?;
+    save(&mut deps.storage, CANVAS_KEY, &canvas)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetCanvas { canvas })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// adds or replaces named svg fragments
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `fragments` - the fragments to add or replace
+fn try_set_fragments<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    fragments: Vec<Fragment>,
+) -> HandleResult {
+    // only allow admins to do this
+    let admins: Vec<CanonicalAddr> = load(&deps.storage, ADMINS_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    let mut frag_store = PrefixedStorage::new(PREFIX_FRAGMENT, &mut deps.storage);
+    for frag in fragments.iter() {
+        frag.validate()?;
+        save(&mut frag_store, frag.name.as_bytes(), &frag.svg)?;
+    }
+    // make sure the variants using these fragments still render
+    let users = render_users(
+        &deps.storage,
+        PREFIX_FRAGMENT_USERS,
+        fragments.iter().map(|f| &f.name),
+    )?;
+    check_config(&deps.storage, &users)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetFragments {
+            status: "success".to_string(),
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// removes named svg fragments
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `names` - names of the fragments to remove
+fn try_remove_fragments<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    names: &[String],
+) -> HandleResult {
+    // only allow admins to do this
+    let admins: Vec<CanonicalAddr> = load(&deps.storage, ADMINS_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    let mut frag_store = PrefixedStorage::new(PREFIX_FRAGMENT, &mut deps.storage);
+    for name in names.iter() {
+        remove(&mut frag_store, name.as_bytes());
+    }
+    // make sure no variant still uses a removed fragment
+    let users = render_users(&deps.storage, PREFIX_FRAGMENT_USERS, names.iter())?;
+    check_config(&deps.storage, &users)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RemoveFragments {
+            status: "success".to_string(),
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// adds or replaces named color palettes
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `palettes` - the palettes to add or replace
+fn try_set_palettes<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    palettes: Vec<Palette>,
+) -> HandleResult {
+    // only allow admins to do this
+    let admins: Vec<CanonicalAddr> = load(&deps.storage, ADMINS_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    let mut stored: Vec<Palette> = may_load(&deps.storage, PALETTES_KEY)?.unwrap_or_else(Vec::new);
+    let names: Vec<String> = palettes.iter().map(|p| p.name.clone()).collect();
+    for pal in palettes.into_iter() {
+        pal.validate()?;
+        if let Some(existing) = stored.iter_mut().find(|p| p.name == pal.name) {
+            *existing = pal;
+        } else {
+            stored.push(pal);
+        }
+    }
+    save(&mut deps.storage, PALETTES_KEY, &stored)?;
+    // make sure the variants using these palettes still render
+    let users = render_users(&deps.storage, PREFIX_PALETTE_USERS, names.iter())?;
+    check_config(&deps.storage, &users)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetPalettes {
+            status: "success".to_string(),
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// removes named color palettes
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `names` - names of the palettes to remove
+fn try_remove_palettes<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    names: &[String],
+) -> HandleResult {
+    // only allow admins to do this
+    let admins: Vec<CanonicalAddr> = load(&deps.storage, ADMINS_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    let mut stored: Vec<Palette> = may_load(&deps.storage, PALETTES_KEY)?.unwrap_or_else(Vec::new);
+    let old_len = stored.len();
+    stored.retain(|p| !names.contains(&p.name));
+    if stored.len() != old_len {
+        save(&mut deps.storage, PALETTES_KEY, &stored)?;
+    }
+    // make sure no variant still uses a removed palette
+    let users = render_users(&deps.storage, PREFIX_PALETTE_USERS, names.iter())?;
+    check_config(&deps.storage, &users)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RemovePalettes {
+            status: "success".to_string(),
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// sets the order the layer categories are drawn in
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `categories` - names of every category in the order they should be drawn
+fn try_set_render_order<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    categories: &[String],
+) -> HandleResult {
+    // only allow admins to do this
+    let admins: Vec<CanonicalAddr> = load(&deps.storage, ADMINS_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    let mut roll: RollConfig = load(&deps.storage, ROLL_CONF_KEY)?;
+    let cat_map = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY_MAP, &deps.storage);
+    let mut render_order: Vec<u8> = Vec::new();
+    for name in categories.iter() {
+        let cat_idx: u8 = may_load(&cat_map, name.as_bytes())?.ok_or_else(|| {
+            StdError::generic_err(format!("Category name:  {} does not exist", name))
+        })?;
+        if render_order.contains(&cat_idx) {
+            return Err(StdError::generic_err(format!(
+                "Category {} is listed more than once",
+                name
+            )));
+        }
+        render_order.push(cat_idx);
+    }
+    if render_order.len() != roll.cat_cnt as usize {
+        return Err(StdError::generic_err(
+            "The render order must list every category",
+        ));
+    }
+    if roll.render_order != render_order {
+        roll.render_order = render_order;
+        save(&mut deps.storage, ROLL_CONF_KEY, &roll)?;
+    }
+    // make sure the changes leave a consistent layer configuration
+    check_config(&deps.storage, &Scope::Roll)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetRenderOrder {
+            status: "success".to_string(),
+        })?),
+    })
+}
+
 /// Returns HandleResult
 ///
 /// sets the common metadata for all NFTs
@@ -327,7 +693,7 @@ fn try_set_metadata<S: Storage, A: Api, Q: Querier>(
 
 /// Returns HandleResult
 ///
-/// changes the name, forced variants, or weight tables of a trait category
+/// changes the name, forced variants, weight tables, or conditionals of a trait category
 ///
 /// # Arguments
 ///
@@ -337,6 +703,7 @@ fn try_set_metadata<S: Storage, A: Api, Q: Querier>(
 /// * `new_name` - optional new name for the category
 /// * `forced_variants` - optional new forced variants
 /// * `weights` - optional new weight tables
+/// * `conditionals` - optional new conditional weight tables and forced variants
 fn try_modify_category<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     sender: &HumanAddr,
@@ -344,6 +711,7 @@ fn try_modify_category<S: Storage, A: Api, Q: Querier>(
     new_name: Option<String>,
     forced_variants: Option<ForcedVariants>,
     weights: Option<Weights>,
+    conditionals: Option<Vec<Conditional>>,
 ) -> HandleResult {
     // only allow admins to do this
     let admins: Vec<CanonicalAddr> = load(&deps.storage, ADMINS_KEY)?;
@@ -352,8 +720,10 @@ fn try_modify_category<S: Storage, A: Api, Q: Querier>(
         return Err(StdError::unauthorized());
     }
     let cat_name_key = name.as_bytes();
+    let mut touched: Vec<u8> = Vec::new();
     let mut cat_map = PrefixedStorage::new(PREFIX_CATEGORY_MAP, &mut deps.storage);
     if let Some(cat_idx) = may_load::<u8, _>(&cat_map, cat_name_key)? {
+        touched.push(cat_idx);
         let mut save_cat = false;
         let cat_key = cat_idx.to_le_bytes();
         let mut may_cat: Option<Category> = None;
@@ -457,6 +827,31 @@ fn try_modify_category<S: Storage, A: Api, Q: Querier>(
             }
             may_cat = Some(cat);
         }
+        if let Some(conds) = conditionals {
+            let mut cat = may_cat.map_or_else(
+                || {
+                    let cat_store = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY, &deps.storage);
+                    may_load::<Category, _>(&cat_store, &cat_key)?.ok_or_else(|| {
+                        StdError::generic_err(format!("Category storage for {} is corrupt", name))
+                    })
+                },
+                Ok,
+            )?;
+            let roll: RollConfig = load(&deps.storage, ROLL_CONF_KEY)?;
+            let new_conds = store_conditionals(
+                &deps.storage,
+                cat_idx,
+                name,
+                cat.normal_weights.len(),
+                StoredArchetype::effective(&deps.storage, &roll)?.as_ref(),
+                conds,
+            )?;
+            if cat.conditionals != new_conds {
+                cat.conditionals = new_conds;
+                save_cat = true;
+            }
+            may_cat = Some(cat);
+        }
         if save_cat {
             let mut cat_store = PrefixedStorage::new(PREFIX_CATEGORY, &mut deps.storage);
             save(
@@ -473,6 +868,8 @@ fn try_modify_category<S: Storage, A: Api, Q: Querier>(
             name
         )));
     }
+    // make sure the changes leave a consistent layer configuration
+    check_config(&deps.storage, &Scope::Categories(touched))?;
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
@@ -503,6 +900,7 @@ fn try_add_categories<S: Storage, A: Api, Q: Querier>(
         return Err(StdError::unauthorized());
     }
     let mut roll: RollConfig = load(&deps.storage, ROLL_CONF_KEY)?;
+    let first_new = roll.cat_cnt;
     for cat_inf in categories.into_iter() {
         let cat_name_key = cat_inf.name.as_bytes();
         let cat_map = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY_MAP, &deps.storage);
@@ -530,6 +928,14 @@ fn try_add_categories<S: Storage, A: Api, Q: Querier>(
         // add the entry to the category map for this category name
         let mut cat_map = PrefixedStorage::new(PREFIX_CATEGORY_MAP, &mut deps.storage);
         save(&mut cat_map, cat_name_key, &roll.cat_cnt)?;
+        let conditionals = store_conditionals(
+            &deps.storage,
+            roll.cat_cnt,
+            &cat_inf.name,
+            normal_weights.len(),
+            StoredArchetype::effective(&deps.storage, &roll)?.as_ref(),
+            cat_inf.conditionals.unwrap_or_else(Vec::new),
+        )?;
         let cat = Category {
             name: cat_inf.name,
             forced_cyclops: cyclops,
@@ -537,15 +943,23 @@ fn try_add_categories<S: Storage, A: Api, Q: Querier>(
             normal_weights,
             jawless_weights,
             cyclops_weights,
+            conditionals,
         };
         let mut cat_store = PrefixedStorage::new(PREFIX_CATEGORY, &mut deps.storage);
         save(&mut cat_store, &cat_key, &cat)?;
+        // new categories are drawn on top of the existing ones
+        roll.render_order.push(roll.cat_cnt);
         roll.cat_cnt = roll
             .cat_cnt
             .checked_add(1)
             .ok_or_else(|| StdError::generic_err("Reached maximum number of trait categories"))?;
     }
     save(&mut deps.storage, ROLL_CONF_KEY, &roll)?;
+    // make sure the changes leave a consistent layer configuration
+    check_config(
+        &deps.storage,
+        &Scope::Categories((first_new..roll.cat_cnt).collect()),
+    )?;
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
@@ -575,12 +989,14 @@ fn try_modify_variants<S: Storage, A: Api, Q: Querier>(
     if !admins.contains(&sender_raw) {
         return Err(StdError::unauthorized());
     }
+    let mut touched: Vec<u8> = Vec::new();
     for cat_inf in modifications.into_iter() {
         let cat_name = cat_inf.category;
         let cat_name_key = cat_name.as_bytes();
         let cat_map = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY_MAP, &deps.storage);
         // if valid category name
         if let Some(cat_idx) = may_load::<u8, _>(&cat_map, cat_name_key)? {
+            touched.push(cat_idx);
             let cat_key = cat_idx.to_le_bytes();
             let cat_store = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY, &deps.storage);
             let mut cat: Category = may_load(&cat_store, &cat_key)?.ok_or_else(|| {
@@ -608,9 +1024,20 @@ fn try_modify_variants<S: Storage, A: Api, Q: Querier>(
                     )?;
                 }
                 let var = Variant {
+                    recolor: map_recolor(
+                        &deps.storage,
+                        &cat_key,
+                        var_mod.modified_variant.recolor.as_ref(),
+                        var_mod.modified_variant.svg.is_some(),
+                    )?,
                     name: var_mod.modified_variant.name,
                     display: var_mod.modified_variant.display_name,
                     svg: var_mod.modified_variant.svg,
+                    animation: var_mod.modified_variant.animation,
+                    render_before: map_render_before(
+                        &deps.storage,
+                        var_mod.modified_variant.render_before.as_deref(),
+                    )?,
                 };
                 let this_wgt = cat
                     .normal_weights
@@ -688,6 +1115,18 @@ fn try_modify_variants<S: Storage, A: Api, Q: Querier>(
                         &cat_name, &var.name
                     )));
                 }
+                let var_store =
+                    ReadonlyPrefixedStorage::multilevel(&[PREFIX_VARIANT, &cat_key], &deps.storage);
+                let old: Option<Variant> = may_load(&var_store, &var_idx.to_le_bytes())?;
+                update_render_users(
+                    &mut deps.storage,
+                    &StoredLayerId {
+                        category: cat_idx,
+                        variant: var_idx,
+                    },
+                    old.as_ref(),
+                    &var,
+                )?;
                 let mut var_store =
                     PrefixedStorage::multilevel(&[PREFIX_VARIANT, &cat_key], &mut deps.storage);
                 save(&mut var_store, &var_idx.to_le_bytes(), &var)?;
@@ -703,6 +1142,8 @@ fn try_modify_variants<S: Storage, A: Api, Q: Querier>(
             )));
         }
     }
+    // make sure the changes leave a consistent layer configuration
+    check_config(&deps.storage, &Scope::Categories(touched))?;
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
@@ -732,10 +1173,12 @@ fn try_add_variants<S: Storage, A: Api, Q: Querier>(
     if !admins.contains(&sender_raw) {
         return Err(StdError::unauthorized());
     }
+    let mut touched: Vec<u8> = Vec::new();
     for cat_inf in variants.into_iter() {
         let cat_name_key = cat_inf.name.as_bytes();
         let cat_map = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY_MAP, &deps.storage);
         if let Some(cat_idx) = may_load::<u8, _>(&cat_map, cat_name_key)? {
+            touched.push(cat_idx);
             let cat_key = cat_idx.to_le_bytes();
             let cat_store = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY, &deps.storage);
             let mut cat: Category = may_load(&cat_store, &cat_key)?.ok_or_else(|| {
@@ -752,6 +1195,15 @@ fn try_add_variants<S: Storage, A: Api, Q: Querier>(
                 None,
                 &cat_inf.name,
             )?;
+            // new variants are not drawn under a condition until given a conditional weight
+            let var_cnt = cat.normal_weights.len();
+            for wgts in cat
+                .conditionals
+                .iter_mut()
+                .filter_map(|c| c.weights.as_mut())
+            {
+                wgts.resize(var_cnt, 0);
+            }
             let mut cat_store = PrefixedStorage::new(PREFIX_CATEGORY, &mut deps.storage);
             save(&mut cat_store, &cat_key, &cat)?;
         } else {
@@ -761,6 +1213,8 @@ fn try_add_variants<S: Storage, A: Api, Q: Querier>(
             )));
         }
     }
+    // make sure the changes leave a consistent layer configuration
+    check_config(&deps.storage, &Scope::Categories(touched))?;
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
@@ -890,6 +1344,13 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
             display_svg,
         ),
         QueryMsg::CommonMetadata { viewer, permit } => query_common_metadata(deps, viewer, permit),
+        QueryMsg::Canvas { viewer, permit } => query_canvas(deps, viewer, permit),
+        QueryMsg::Fragments {
+            viewer,
+            permit,
+            names,
+        } => query_fragments(deps, viewer, permit, names),
+        QueryMsg::Palettes { viewer, permit } => query_palettes(deps, viewer, permit),
         QueryMsg::RollConfig { viewer, permit } => query_roll_config(deps, viewer, permit),
         QueryMsg::Dependencies {
             viewer,
@@ -903,6 +1364,13 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
             start_at,
             limit,
         } => query_hiders(deps, viewer, permit, start_at, limit),
+        QueryMsg::Exclusions {
+            viewer,
+            permit,
+            start_at,
+            limit,
+        } => query_exclusions(deps, viewer, permit, start_at, limit),
+        QueryMsg::ValidateConfig { viewer, permit } => query_validate_config(deps, viewer, permit),
         QueryMsg::NewGenes {
             viewer,
             height,
@@ -910,13 +1378,31 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
             sender,
             entropy,
             backgrounds,
-        } => query_new_gene(deps, viewer, height, time, &sender, &entropy, backgrounds),
+            senders,
+        } => query_new_gene(
+            deps,
+            viewer,
+            height,
+            time,
+            &sender,
+            &entropy,
+            backgrounds,
+            senders,
+        ),
         QueryMsg::TokenMetadata {
             viewer,
             permit,
             image,
         } => query_token_metadata(deps, viewer, permit, &image),
         QueryMsg::ServeAlchemy { viewer } => query_serve_alchemy(deps, viewer),
+        QueryMsg::TraitCounts {} => query_trait_counts(&deps.storage),
+        QueryMsg::SimulateGenes {
+            viewer,
+            permit,
+            seed,
+            background,
+            count,
+        } => query_simulate_genes(deps, viewer, permit, &seed, &background, count),
     };
     pad_query_result(response, BLOCK_SIZE)
 }
@@ -969,6 +1455,8 @@ fn query_serve_alchemy<S: Storage, A: Api, Q: Querier>(
 /// * `sender` - a reference to the address sending the mint tx
 /// * `entropy` - entropy string slice for randomization
 /// * `backgrounds` - background layer variant names
+/// * `senders` - optional address for each background used in place of the sender
+#[allow(clippy::too_many_arguments)]
 fn query_new_gene<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     viewer: ViewerInfo,
@@ -977,6 +1465,7 @@ fn query_new_gene<S: Storage, A: Api, Q: Querier>(
     sender: &HumanAddr,
     entropy: &str,
     backgrounds: Vec<String>,
+    senders: Option<Vec<HumanAddr>>,
 ) -> QueryResult {
     let (querier, _) = get_querier(deps, Some(viewer), None)?;
     // only allow minters to call this
@@ -988,77 +1477,155 @@ fn query_new_gene<S: Storage, A: Api, Q: Querier>(
     let prng_seed: Vec<u8> = load(&deps.storage, PRNG_SEED_KEY)?;
     let rng_entropy = extend_entropy(height, time, sender, entropy.as_bytes());
     let mut rng = Prng::new(&prng_seed, &rng_entropy);
-    let roll: RollConfig = load(&deps.storage, ROLL_CONF_KEY)?;
-    let depends: Vec<StoredDependencies> =
-        may_load(&deps.storage, DEPENDENCIES_KEY)?.unwrap_or_else(Vec::new);
-    let hiders: Vec<StoredDependencies> =
-        may_load(&deps.storage, HIDERS_KEY)?.unwrap_or_else(Vec::new);
-    let mut cat_cache: Vec<RefCache<Category>> = Vec::new();
-    let mut none_cache: Vec<StoredLayerId> = Vec::new();
-    let mut skull_cache: Vec<RefCache<Variant>> = Vec::new();
-    let mut eye_type_cache: Vec<RefCache<Variant>> = Vec::new();
-    let mut back_cache: Vec<BackCache> = Vec::new();
-    let mut chin_cache: Vec<BackCache> = Vec::new();
+    let mut roller = GeneRoller::new(&deps.storage)?;
     let mut genes: Vec<GeneInfo> = Vec::new();
-    let mut uniques: Vec<Vec<u8>> = Vec::new();
-    // background is always the first layer
-    let background_map = ReadonlyPrefixedStorage::multilevel(
-        &[PREFIX_VARIANT_MAP, &0u8.to_le_bytes()],
-        &deps.storage,
-    );
-    let cat_map = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY_MAP, &deps.storage);
-    let eye_type_idx: u8 = may_load(&cat_map, "Eye Type".as_bytes())?
-        .ok_or_else(|| StdError::generic_err("Eye Type layer category not found"))?;
-    let chin_idx: u8 = may_load(&cat_map, "Jaw Type".as_bytes())?
-        .ok_or_else(|| StdError::generic_err("Jaw Type layer category not found"))?;
-    let skull_idx: u8 = may_load(&cat_map, "Skull".as_bytes())?
-        .ok_or_else(|| StdError::generic_err("Skull layer category not found"))?;
-    // create the gene seed
-    let mut gene_seed: Vec<u8> = vec![255; roll.cat_cnt as usize];
-    // any layers being skipped should be set to None
-    for skip_cat in roll.skip.iter() {
-        let none_idx = use_none_cache(&deps.storage, *skip_cat, &mut none_cache)?;
-        gene_seed[*skip_cat as usize] = none_idx;
-    }
-
-    let archetype_idxs = vec![skull_idx, chin_idx, eye_type_idx];
-    for back in backgrounds.into_iter() {
-        let background_idx = use_back_cache(&background_map, &back, &mut back_cache)?;
-        gene_seed[0] = background_idx;
-        let mut roll_it = true;
-        while roll_it {
-            let (reroll, current_image, genetic_image, unique_check) = new_gene_impl(
-                &deps.storage,
-                &mut rng,
-                &roll,
-                &depends,
-                &hiders,
-                eye_type_idx,
-                chin_idx,
-                skull_idx,
-                &mut none_cache,
-                &mut cat_cache,
-                &mut skull_cache,
-                &mut eye_type_cache,
-                &mut chin_cache,
-                &gene_seed,
-                &mut uniques,
-                &archetype_idxs,
-            )?;
-            if !reroll {
-                genes.push(GeneInfo {
-                    current_image,
-                    genetic_image,
-                    unique_check,
-                });
-            }
-            roll_it = reroll;
+    if senders
+        .as_ref()
+        .filter(|s| s.len() != backgrounds.len())
+        .is_some()
+    {
+        return Err(StdError::generic_err(
+            "There must be one sender for each background",
+        ));
+    }
+    for (i, back) in backgrounds.into_iter().enumerate() {
+        // each gene with its own sender gets its own randomization
+        if let Some(gene_sender) = senders.as_ref().and_then(|s| s.get(i)) {
+            let mut gene_entropy = entropy.as_bytes().to_vec();
+            gene_entropy.extend_from_slice(&(i as u32).to_be_bytes());
+            rng = Prng::new(
+                &prng_seed,
+                &extend_entropy(height, time, gene_sender, &gene_entropy),
+            );
         }
+        genes.push(roller.roll_gene(&deps.storage, &mut rng, &back)?);
     }
 
     to_binary(&QueryAnswer::NewGenes { genes })
 }
 
+/// Returns QueryResult displaying the distribution of traits produced by rolling a number
+/// of genes with a provided seed.  Uniqueness is only checked against the existing genes
+/// and the other simulated genes, and nothing is recorded
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `viewer` - optional address and key making an authenticated query request
+/// * `permit` - optional permit with "owner" permission
+/// * `seed` - seed string slice for randomization
+/// * `background` - name of the background variant to use
+/// * `count` - number of genes to roll
+fn query_simulate_genes<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    viewer: Option<ViewerInfo>,
+    permit: Option<Permit>,
+    seed: &str,
+    background: &str,
+    count: u16,
+) -> QueryResult {
+    // only allow admins to do this
+    check_admin(deps, viewer, permit)?;
+    let mut rng = Prng::new(seed.as_bytes(), b"simulate");
+    // use the same setup as minting so the simulation can not drift from it
+    let mut roller = GeneRoller::new(&deps.storage)?;
+    // tally of each variant in each category
+    let mut tallies: Vec<Vec<u32>> = Vec::new();
+    let mut jawless = 0u32;
+    let mut cyclops = 0u32;
+    for _ in 0..count {
+        let gene = roller.roll_gene(&deps.storage, &mut rng, background)?;
+        tally_traits(&mut tallies, &gene.genetic_image);
+        // the uniqueness mask ends with the cyclops and jawless flags
+        let flags = gene.unique_check.len().saturating_sub(2);
+        cyclops += gene.unique_check.get(flags).copied().unwrap_or(0) as u32;
+        jawless += gene.unique_check.get(flags + 1).copied().unwrap_or(0) as u32;
+    }
+    to_binary(&QueryAnswer::SimulateGenes {
+        count,
+        histograms: histograms(&deps.storage, &tallies)?,
+        jawless,
+        cyclops,
+        rerolls: roller.rerolls,
+        restarts: roller.restarts,
+    })
+}
+
+/// Returns QueryResult displaying the number of minted genes with each trait variant
+///
+/// # Arguments
+///
+/// * `storage` - reference to the contract's storage
+fn query_trait_counts<S: ReadonlyStorage>(storage: &S) -> QueryResult {
+    let minted: MintedTraits = may_load(storage, MINTED_TRAITS_KEY)?.unwrap_or(MintedTraits {
+        total: 0,
+        counts: Vec::new(),
+        backfilled: false,
+    });
+    to_binary(&QueryAnswer::TraitCounts {
+        total: minted.total,
+        histograms: histograms(storage, &minted.counts)?,
+        complete: minted.backfilled,
+    })
+}
+
+/// Returns StdResult<Vec<CategoryHistogram>> from mapping the tallies of each variant
+/// index to their category and variant names
+///
+/// # Arguments
+///
+/// * `storage` - reference to the contract's storage
+/// * `tallies` - number of times each variant index appeared in each category index
+fn histograms<S: ReadonlyStorage>(
+    storage: &S,
+    tallies: &[Vec<u32>],
+) -> StdResult<Vec<CategoryHistogram>> {
+    let roll: RollConfig = load(storage, ROLL_CONF_KEY)?;
+    let cat_store = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY, storage);
+    let mut histograms: Vec<CategoryHistogram> = Vec::new();
+    for cat_idx in 0..roll.cat_cnt {
+        let cat_key = cat_idx.to_le_bytes();
+        let cat: Category = may_load(&cat_store, &cat_key)?
+            .ok_or_else(|| StdError::generic_err("Category storage is corrupt"))?;
+        let var_store = ReadonlyPrefixedStorage::multilevel(&[PREFIX_VARIANT, &cat_key], storage);
+        let tally = tallies.get(cat_idx as usize);
+        let mut variants: Vec<VariantCount> = Vec::new();
+        for var_idx in 0..cat.normal_weights.len() {
+            let var: Variant = may_load(&var_store, &(var_idx as u8).to_le_bytes())?
+                .ok_or_else(|| StdError::generic_err("Variant storage is corrupt"))?;
+            variants.push(VariantCount {
+                variant: var.name,
+                count: tally.and_then(|t| t.get(var_idx)).copied().unwrap_or(0),
+            });
+        }
+        histograms.push(CategoryHistogram {
+            category: cat.name,
+            variants,
+        });
+    }
+    Ok(histograms)
+}
+
+/// adds a genetic image's variants to the tallies of each variant index in each
+/// category index
+///
+/// # Arguments
+///
+/// * `tallies` - number of times each variant index appeared in each category index
+/// * `image` - genetic image indices
+fn tally_traits(tallies: &mut Vec<Vec<u32>>, image: &[u8]) {
+    if tallies.len() < image.len() {
+        tallies.resize(image.len(), Vec::new());
+    }
+    for (tally, var) in tallies.iter_mut().zip(image.iter()) {
+        let var = *var as usize;
+        if tally.len() <= var {
+            tally.resize(var + 1, 0);
+        }
+        tally[var] += 1;
+    }
+}
+
 /// Returns QueryResult displaying the layer categories that should be skipped when rolling
 /// and the ones that must be rolled first (and the total number of categories)
 ///
@@ -1087,11 +1654,25 @@ fn query_roll_config<S: Storage, A: Api, Q: Querier>(
         })
         .collect::<StdResult<Vec<String>>>()?;
 
+    let render_order = roll
+        .render_order
+        .iter()
+        .map(|u| {
+            may_load::<Category, _>(&cat_store, &u.to_le_bytes())?
+                .ok_or_else(|| StdError::generic_err("Category storage is corrupt"))
+                .map(|r| r.name)
+        })
+        .collect::<StdResult<Vec<String>>>()?;
+
     to_binary(&QueryAnswer::RollConfig {
         category_count: roll.cat_cnt,
         skip,
         jaw_weight: roll.jaw_weights[0],
         jawless_weight: roll.jaw_weights[1],
+        archetype: StoredArchetype::effective(&deps.storage, &roll)?
+            .map(|a| a.to_display(&deps.storage))
+            .transpose()?,
+        render_order,
     })
 }
 
@@ -1163,6 +1744,67 @@ fn query_hiders<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Returns QueryResult displaying the trait variants that can not appear with other variants
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `viewer` - optional address and key making an authenticated query request
+/// * `permit` - optional permit with "owner" permission
+/// * `start_at` - optional exclusion index to start the display
+/// * `limit` - optional max number of exclusions to display
+fn query_exclusions<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    viewer: Option<ViewerInfo>,
+    permit: Option<Permit>,
+    start_at: Option<u16>,
+    limit: Option<u16>,
+) -> QueryResult {
+    // only allow admins to do this
+    check_admin(deps, viewer, permit)?;
+    let max = limit.unwrap_or(100);
+    let start = start_at.unwrap_or(0);
+    let exclusions: Vec<StoredDependencies> =
+        may_load(&deps.storage, EXCLUSIONS_KEY)?.unwrap_or_else(Vec::new);
+    let count = exclusions.len() as u16;
+    to_binary(&QueryAnswer::Exclusions {
+        count,
+        exclusions: exclusions
+            .iter()
+            .skip(start as usize)
+            .take(max as usize)
+            .map(|d| d.to_display(&deps.storage))
+            .collect::<StdResult<Vec<Dependencies>>>()?,
+    })
+}
+
+/// Returns QueryResult displaying any problems with the layer configuration
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `viewer` - optional address and key making an authenticated query request
+/// * `permit` - optional permit with "owner" permission
+fn query_validate_config<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    viewer: Option<ViewerInfo>,
+    permit: Option<Permit>,
+) -> QueryResult {
+    // only allow admins to do this
+    check_admin(deps, viewer, permit)?;
+    let mut problems = validate_config(&deps.storage)?;
+    // the archetype can only be set after its categories are added, so it is not required
+    // when validating changes, but genes can not be rolled without it
+    let roll: RollConfig = load(&deps.storage, ROLL_CONF_KEY)?;
+    if StoredArchetype::effective(&deps.storage, &roll)?.is_none() {
+        problems.push("The archetype categories have not been set".to_string());
+    }
+    to_binary(&QueryAnswer::ValidateConfig {
+        valid: problems.is_empty(),
+        problems,
+    })
+}
+
 /// Returns QueryResult displaying a layer variant
 ///
 /// # Arguments
@@ -1291,12 +1933,18 @@ fn query_category<S: Storage, A: Api, Q: Querier>(
                 .map(|v| v.name)
         })
         .transpose()?;
+    let conditionals = cat
+        .conditionals
+        .iter()
+        .map(|c| c.to_display(&deps.storage, cat_idx))
+        .collect::<StdResult<Vec<Conditional>>>()?;
     to_binary(&QueryAnswer::Category {
         category_count: roll.cat_cnt,
         index: cat_idx,
         name: cat.name,
         forced_cyclops,
         forced_jawless,
+        conditionals,
         variant_count,
         variants,
     })
@@ -1374,15 +2022,30 @@ fn query_token_metadata<S: Storage, A: Api, Q: Querier>(
     });
     let mut xten = public_metadata.extension.unwrap_or_default();
     let roll: RollConfig = load(&deps.storage, ROLL_CONF_KEY)?;
-    let mut image_data = r###"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 -0.5 24 24" shape-rendering="crispEdges">"###.to_string();
+    let canvas: SvgCanvas = may_load(&deps.storage, CANVAS_KEY)?.unwrap_or_default();
+    let mut image_data = canvas.open();
     let mut attributes: Vec<Trait> = Vec::new();
     let cat_store = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY, &deps.storage);
     let mut trait_cnt = 0u8;
     let mut revealed = 0u8;
-    // get the hair category index
-    let cat_map = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY_MAP, &deps.storage);
-    let hair_idx: u8 = may_load(&cat_map, "Hair".as_bytes())?
-        .ok_or_else(|| StdError::generic_err("Hair layer category not found"))?;
+    // rarity score is the sum of the inverse frequencies of the revealed traits.  It is only
+    // shown once the genes minted before the counts were kept have been counted
+    let minted: Option<MintedTraits> =
+        may_load::<MintedTraits, _>(&deps.storage, MINTED_TRAITS_KEY)?.filter(|m| m.backfilled);
+    let mut rarity = 0u64;
+    // get the index of the category that shows a placeholder when unknown
+    let placeholder_idx =
+        StoredArchetype::effective(&deps.storage, &roll)?.and_then(|a| a.placeholder);
+    // layers are drawn in render order, and a variant drawn behind another category goes
+    // just before that category's layer
+    let render_pos = |idx: u8| {
+        roll.render_order
+            .iter()
+            .position(|c| *c == idx)
+            .unwrap_or(idx as usize)
+    };
+    let mut layers: Vec<(usize, bool, String)> = Vec::new();
+    let mut animated = false;
 
     for (cat_idx, var_idx) in image.iter().enumerate() {
         let cat_key = (cat_idx as u8).to_le_bytes();
@@ -1390,16 +2053,19 @@ fn query_token_metadata<S: Storage, A: Api, Q: Querier>(
             .ok_or_else(|| StdError::generic_err("Category storage is corrupt"))?;
         let disp_trait = !roll.skip.contains(&(cat_idx as u8));
         // 255 means not revealed
-        if *var_idx != 255 || cat_idx == hair_idx as usize {
+        if *var_idx != 255 || placeholder_idx == Some(cat_idx as u8) {
             let (mod_var_idx, is_unknown) = if *var_idx == 255 {
-                // if this is unknown Hair
+                // if this is the unknown placeholder category
                 let var_map = ReadonlyPrefixedStorage::multilevel(
                     &[PREFIX_VARIANT_MAP, &cat_key],
                     &deps.storage,
                 );
                 (
                     may_load(&var_map, "None".as_bytes())?.ok_or_else(|| {
-                        StdError::generic_err("Missing None variant of Hair Category")
+                        StdError::generic_err(format!(
+                            "Missing None variant of {} Category",
+                            cat.name
+                        ))
                     })?,
                     true,
                 )
@@ -1414,10 +2080,39 @@ fn query_token_metadata<S: Storage, A: Api, Q: Querier>(
                 ReadonlyPrefixedStorage::multilevel(&[PREFIX_VARIANT, &cat_key], &deps.storage);
             let var: Variant = may_load(&var_store, &mod_var_idx.to_le_bytes())?
                 .ok_or_else(|| StdError::generic_err("Variant storage is corrupt"))?;
-            image_data.push_str(&var.svg.unwrap_or_default());
+            let svg = variant_svg(&deps.storage, cat_idx as u8, &var)?;
+            // animations are only drawn if enabled for the whole collection
+            let animation = var
+                .animation
+                .as_ref()
+                .filter(|_| canvas.animations_enabled)
+                .map(|a| expand_fragments(&deps.storage, a))
+                .transpose()?;
+            if svg.is_some() || animation.is_some() {
+                let pos = var.render_before.unwrap_or(cat_idx as u8);
+                let mut layer = svg.unwrap_or_default();
+                if let Some(anim) = animation {
+                    layer.push_str(&anim);
+                    animated = true;
+                }
+                layers.push((render_pos(pos), var.render_before.is_none(), layer));
+            }
             let value = if is_unknown {
                 "???".to_string()
             } else {
+                if disp_trait {
+                    if let Some(mt) = minted.as_ref() {
+                        let cnt = mt
+                            .counts
+                            .get(cat_idx)
+                            .and_then(|c| c.get(*var_idx as usize))
+                            .copied()
+                            .unwrap_or(0);
+                        if cnt != 0 {
+                            rarity += (mt.total as u64) * 100 / (cnt as u64);
+                        }
+                    }
+                }
                 var.display
             };
             if disp_trait {
@@ -1446,6 +2141,14 @@ fn query_token_metadata<S: Storage, A: Api, Q: Querier>(
         value: format!("{}", hidden),
         max_value: None,
     });
+    if minted.filter(|m| m.total != 0).is_some() {
+        attributes.push(Trait {
+            display_type: Some("number".to_string()),
+            trait_type: Some("Rarity Score".to_string()),
+            value: format!("{}.{:02}", rarity / 100, rarity % 100),
+            max_value: None,
+        });
+    }
     // this svg server is only used by pre-alchemy skulls
     attributes.push(Trait {
         display_type: None,
@@ -1453,7 +2156,19 @@ fn query_token_metadata<S: Storage, A: Api, Q: Querier>(
         value: "Raw".to_string(),
         max_value: None,
     });
+    // the sort is stable, so layers in the same position keep their category order
+    layers.sort_by_key(|l| (l.0, l.1));
+    for (_, _, svg) in layers.into_iter() {
+        image_data.push_str(&svg);
+    }
     image_data.push_str("</svg>");
+    // also provide the animated svg as a data uri for viewers that only animate media urls
+    if animated {
+        xten.animation_url = Some(format!(
+            "data:image/svg+xml;base64,{}",
+            base64::encode(image_data.as_bytes())
+        ));
+    }
     xten.image_data = Some(image_data);
     xten.attributes = Some(attributes);
     public_metadata.extension = Some(xten);
@@ -1464,6 +2179,86 @@ fn query_token_metadata<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Returns QueryResult displaying the svg canvas that wraps every image
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `viewer` - optional address and key making an authenticated query request
+/// * `permit` - optional permit with "owner" permission
+fn query_canvas<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    viewer: Option<ViewerInfo>,
+    permit: Option<Permit>,
+) -> QueryResult {
+    // only allow authorized addresses to do this
+    let (querier, _) = get_querier(deps, viewer, permit)?;
+    let minters: Vec<CanonicalAddr> =
+        may_load(&deps.storage, MINTERS_KEY)?.unwrap_or_else(Vec::new);
+    if !minters.contains(&querier) {
+        let viewers: Vec<CanonicalAddr> =
+            may_load(&deps.storage, VIEWERS_KEY)?.unwrap_or_else(Vec::new);
+        if !viewers.contains(&querier) {
+            let admins: Vec<CanonicalAddr> = load(&deps.storage, ADMINS_KEY)?;
+            if !admins.contains(&querier) {
+                return Err(StdError::unauthorized());
+            }
+        }
+    }
+    let canvas: SvgCanvas = may_load(&deps.storage, CANVAS_KEY)?.unwrap_or_default();
+
+    to_binary(&QueryAnswer::Canvas { canvas })
+}
+
+/// Returns QueryResult displaying named svg fragments
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `viewer` - optional address and key making an authenticated query request
+/// * `permit` - optional permit with "owner" permission
+/// * `names` - names of the fragments to display
+fn query_fragments<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    viewer: Option<ViewerInfo>,
+    permit: Option<Permit>,
+    names: Vec<String>,
+) -> QueryResult {
+    // only allow admins to do this
+    check_admin(deps, viewer, permit)?;
+    let frag_store = ReadonlyPrefixedStorage::new(PREFIX_FRAGMENT, &deps.storage);
+    let fragments = names
+        .into_iter()
+        .map(|name| {
+            let svg: String = may_load(&frag_store, name.as_bytes())?.ok_or_else(|| {
+                StdError::generic_err(format!("Fragment {} does not exist", name))
+            })?;
+            Ok(Fragment { name, svg })
+        })
+        .collect::<StdResult<Vec<Fragment>>>()?;
+
+    to_binary(&QueryAnswer::Fragments { fragments })
+}
+
+/// Returns QueryResult displaying the color palettes
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `viewer` - optional address and key making an authenticated query request
+/// * `permit` - optional permit with "owner" permission
+fn query_palettes<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    viewer: Option<ViewerInfo>,
+    permit: Option<Permit>,
+) -> QueryResult {
+    // only allow admins to do this
+    check_admin(deps, viewer, permit)?;
+    let palettes: Vec<Palette> = may_load(&deps.storage, PALETTES_KEY)?.unwrap_or_else(Vec::new);
+
+    to_binary(&QueryAnswer::Palettes { palettes })
+}
+
 /// Returns QueryResult displaying the metadata common to all NFTs
 ///
 /// # Arguments
@@ -1734,9 +2529,17 @@ fn add_variants<S: Storage>(
             }
         }
         let var = Variant {
+            recolor: map_recolor(
+                &*storage,
+                cat_key,
+                var_inf.recolor.as_ref(),
+                var_inf.svg.is_some(),
+            )?,
             name: var_inf.name,
             display: var_inf.display_name,
             svg: var_inf.svg,
+            animation: var_inf.animation,
+            render_before: map_render_before(&*storage, var_inf.render_before.as_deref())?,
         };
         let var_name_key = var.name.as_bytes();
         let mut var_map = PrefixedStorage::multilevel(&[PREFIX_VARIANT_MAP, cat_key], storage);
@@ -1795,6 +2598,12 @@ fn add_variants<S: Storage>(
             }
         }
         save(&mut var_map, var_name_key, &var_cnt)?;
+        // the category key is the category index
+        let layer = StoredLayerId {
+            category: cat_key[0],
+            variant: var_cnt,
+        };
+        update_render_users(storage, &layer, None, &var)?;
         let mut var_store = PrefixedStorage::multilevel(&[PREFIX_VARIANT, cat_key], storage);
         save(&mut var_store, &var_cnt.to_le_bytes(), &var)?;
         var_cnt = var_cnt.checked_add(1).ok_or_else(|| {
@@ -1821,6 +2630,220 @@ fn add_variants<S: Storage>(
     Ok((cyclops_idx, jawless_idx))
 }
 
+/// Returns StdResult<Vec<StoredConditional>>
+///
+/// verifies a category's conditional weight tables and forced variants, and converts them
+/// to their stored form
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `cat_idx` - index of the category the conditionals belong to
+/// * `cat_name` - name of the category the conditionals belong to
+/// * `var_cnt` - number of variants in the category
+/// * `archetype` - optional archetype, which determines the roll order
+/// * `conditionals` - the conditional weight tables and forced variants
+fn store_conditionals<S: ReadonlyStorage>(
+    storage: &S,
+    cat_idx: u8,
+    cat_name: &str,
+    var_cnt: usize,
+    archetype: Option<&StoredArchetype>,
+    conditionals: Vec<Conditional>,
+) -> StdResult<Vec<StoredConditional>> {
+    let order = roll_order(archetype, cat_idx.saturating_add(1));
+    conditionals
+        .iter()
+        .map(|cond| {
+            if cond.weights.is_some() == cond.forced.is_some() {
+                return Err(StdError::generic_err(format!(
+                    "A conditional for category {} must have either a weight table or a forced variant",
+                    cat_name
+                )));
+            }
+            if cond.weights.as_ref().filter(|w| w.len() != var_cnt).is_some() {
+                return Err(StdError::generic_err(format!(
+                    "Conditional weight table for category {} has incorrect length",
+                    cat_name
+                )));
+            }
+            // a weight table is drawn from, so it must have a nonzero total that fits in a u16
+            if let Some(weights) = cond.weights.as_ref() {
+                let total: u32 = weights.iter().map(|w| *w as u32).sum();
+                if total == 0 || total > u16::MAX as u32 {
+                    return Err(StdError::generic_err(format!(
+                        "Conditional weight table for category {} must total between 1 and 65535",
+                        cat_name
+                    )));
+                }
+            }
+            let stored = cond.to_stored(storage, cat_idx)?;
+            // the condition must be rolled before this category
+            let cond_cat = stored.condition.category;
+            if !rolled_before(&order, cond_cat, cat_idx) {
+                return Err(StdError::generic_err(format!(
+                    "Category {} is not rolled before category {}",
+                    cond.condition.category, cat_name
+                )));
+            }
+            Ok(stored)
+        })
+        .collect()
+}
+
+/// Returns StdResult<Option<u8>> of the index of the category a variant should be drawn behind
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `render_before` - optional name of the category the variant should be drawn behind
+fn map_render_before<S: ReadonlyStorage>(
+    storage: &S,
+    render_before: Option<&str>,
+) -> StdResult<Option<u8>> {
+    let cat_map = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY_MAP, storage);
+    render_before
+        .map(|c| {
+            may_load::<u8, _>(&cat_map, c.as_bytes())?.ok_or_else(|| {
+                StdError::generic_err(format!("Category name:  {} does not exist", c))
+            })
+        })
+        .transpose()
+}
+
+/// Returns StdResult<()>
+///
+/// updates the lists of variants that use each fragment and palette after a variant changes
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `layer` - a reference to the StoredLayerId of the variant
+/// * `old` - optional reference to the variant before it changed
+/// * `new` - a reference to the changed variant
+fn update_render_users<S: Storage>(
+    storage: &mut S,
+    layer: &StoredLayerId,
+    old: Option<&Variant>,
+    new: &Variant,
+) -> StdResult<()> {
+    // collect the fragments and palettes used by a variant's svg and animation
+    let refs = |var: Option<&Variant>| {
+        let mut frags: Vec<String> = Vec::new();
+        let mut pals: Vec<String> = Vec::new();
+        for svg in var
+            .into_iter()
+            .flat_map(|v| v.svg.iter().chain(v.animation.iter()))
+        {
+            let (f, p) = references(svg);
+            for name in f.into_iter() {
+                if !frags.contains(&name) {
+                    frags.push(name);
+                }
+            }
+            for name in p.into_iter() {
+                if !pals.contains(&name) {
+                    pals.push(name);
+                }
+            }
+        }
+        (frags, pals)
+    };
+    let (old_frags, old_pals) = refs(old);
+    let (new_frags, new_pals) = refs(Some(new));
+    for (prefix, old_names, new_names) in vec![
+        (PREFIX_FRAGMENT_USERS, old_frags, new_frags),
+        (PREFIX_PALETTE_USERS, old_pals, new_pals),
+    ]
+    .into_iter()
+    {
+        let mut users_store = PrefixedStorage::new(prefix, storage);
+        for name in old_names.iter().filter(|n| !new_names.contains(n)) {
+            let mut users: Vec<StoredLayerId> =
+                may_load(&users_store, name.as_bytes())?.unwrap_or_else(Vec::new);
+            users.retain(|u| u != layer);
+            if users.is_empty() {
+                remove(&mut users_store, name.as_bytes());
+            } else {
+                save(&mut users_store, name.as_bytes(), &users)?;
+            }
+        }
+        for name in new_names.iter().filter(|n| !old_names.contains(n)) {
+            let mut users: Vec<StoredLayerId> =
+                may_load(&users_store, name.as_bytes())?.unwrap_or_else(Vec::new);
+            users.push(layer.clone());
+            save(&mut users_store, name.as_bytes(), &users)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns StdResult<Scope> of the variants that use any of the named fragments or palettes
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `prefix` - storage prefix of the lists of fragment or palette users
+/// * `names` - names of the fragments or palettes
+fn render_users<'a, S: ReadonlyStorage>(
+    storage: &S,
+    prefix: &[u8],
+    names: impl Iterator<Item = &'a String>,
+) -> StdResult<Scope> {
+    let users_store = ReadonlyPrefixedStorage::new(prefix, storage);
+    let mut layers: Vec<StoredLayerId> = Vec::new();
+    for name in names {
+        let users: Vec<StoredLayerId> =
+            may_load(&users_store, name.as_bytes())?.unwrap_or_else(Vec::new);
+        for user in users.into_iter() {
+            if !layers.contains(&user) {
+                layers.push(user);
+            }
+        }
+    }
+    Ok(Scope::Variants(layers))
+}
+
+/// Returns StdResult<Option<StoredRecolor>> of a variant's base variant index and parsed color
+/// swaps
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `cat_key` - index of the category the variant belongs to as a byte slice
+/// * `recolor` - optional base variant name and color swaps
+/// * `has_svg` - true if the variant also has its own svg
+fn map_recolor<S: ReadonlyStorage>(
+    storage: &S,
+    cat_key: &[u8],
+    recolor: Option<&Recolor>,
+    has_svg: bool,
+) -> StdResult<Option<StoredRecolor>> {
+    recolor
+        .map(|r| {
+            if has_svg {
+                return Err(StdError::generic_err(format!(
+                    "A recolor of {} can not also have its own svg",
+                    r.base
+                )));
+            }
+            let var_map =
+                ReadonlyPrefixedStorage::multilevel(&[PREFIX_VARIANT_MAP, cat_key], storage);
+            let base: u8 = may_load(&var_map, r.base.as_bytes())?.ok_or_else(|| {
+                StdError::generic_err(format!("Base variant {} does not exist", r.base))
+            })?;
+            Ok(StoredRecolor {
+                base,
+                swaps: r
+                    .swaps
+                    .iter()
+                    .map(|s| s.to_stored())
+                    .collect::<StdResult<Vec<StoredColorSwap>>>()?,
+            })
+        })
+        .transpose()
+}
+
 /// Returns StdResult<Option<Metadata>>
 ///
 /// filter metadata to error if both token_uri and extension are present, or to be
@@ -1999,6 +3022,47 @@ fn try_process_dep_list<S: Storage, A: Api, Q: Querier>(
             }
         }
     };
+    // make sure the changes leave a consistent layer configuration
+    check_config(&deps.storage, &Scope::Lists)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&resp)?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// adds or removes trait variants that can not appear with other trait variants
+///
+/// # Arguments
+///
+/// * `deps` - a mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `update_list` - list of exclusions to add or remove
+/// * `is_add` - true if the exclusions are being added
+fn try_process_exclusions<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    update_list: &[Dependencies],
+    is_add: bool,
+) -> HandleResult {
+    // only allow admins to do this
+    let admins: Vec<CanonicalAddr> = load(&deps.storage, ADMINS_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if !admins.contains(&sender_raw) {
+        return Err(StdError::unauthorized());
+    }
+    let status = "success".to_string();
+    let resp = if is_add {
+        add_dependencies(&mut deps.storage, update_list, EXCLUSIONS_KEY)?;
+        HandleAnswer::AddExclusions { status }
+    } else {
+        remove_dependencies(&mut deps.storage, update_list, EXCLUSIONS_KEY)?;
+        HandleAnswer::RemoveExclusions { status }
+    };
+    // make sure the changes leave a consistent layer configuration
+    check_config(&deps.storage, &Scope::Lists)?;
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
@@ -2006,6 +3070,84 @@ fn try_process_dep_list<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Returns StdError for a gene that had to start over too many times
+fn too_many_restarts() -> StdError {
+    StdError::generic_err(format!(
+        "Unable to roll a unique gene that satisfies the exclusions after {} attempts",
+        MAX_RESTARTS
+    ))
+}
+
+/// Returns bool true if any variant in the genetic image can not appear with the others
+///
+/// # Arguments
+///
+/// * `genetic` - genetic image indices
+/// * `exclusions` - list of variants that can not appear with other variants
+fn violates_exclusions(genetic: &[u8], exclusions: &[StoredDependencies]) -> bool {
+    genetic
+        .iter()
+        .enumerate()
+        .any(|(cat, var)| excluded_variants(cat as u8, genetic, exclusions).contains(var))
+}
+
+/// Returns Option<u8> of the variant drawn from a weight table after removing the excluded
+/// variants, or None if every variant is excluded
+///
+/// # Arguments
+///
+/// * `prng` - a mutable reference to the prng
+/// * `weights` - weight table
+/// * `excluded` - indices of the variants that can not be drawn
+fn draw_allowed(prng: &mut Prng, weights: &[u16], excluded: &[u8]) -> Option<u8> {
+    if excluded.is_empty() {
+        return Some(draw_variant(prng, weights));
+    }
+    let allowed: Vec<u16> = weights
+        .iter()
+        .enumerate()
+        .map(|(i, w)| if excluded.contains(&(i as u8)) { 0 } else { *w })
+        .collect();
+    if allowed.iter().all(|w| *w == 0) {
+        None
+    } else {
+        Some(draw_variant(prng, &allowed))
+    }
+}
+
+/// Returns Vec<u8>
+///
+/// returns the indices of the variants of a category that can not appear with the variants
+/// already in the genetic image
+///
+/// # Arguments
+///
+/// * `category` - index of the category being rolled
+/// * `genetic` - genetic image indices
+/// * `exclusions` - list of variants that can not appear with other variants
+fn excluded_variants(category: u8, genetic: &[u8], exclusions: &[StoredDependencies]) -> Vec<u8> {
+    let chosen = |l: &StoredLayerId| {
+        l.category != category && genetic.get(l.category as usize) == Some(&l.variant)
+    };
+    let mut excluded: Vec<u8> = Vec::new();
+    for excl in exclusions.iter() {
+        // exclusions apply in both directions
+        if excl.id.category == category {
+            if excl.correlated.iter().any(|l| chosen(l)) {
+                excluded.push(excl.id.variant);
+            }
+        } else if chosen(&excl.id) {
+            excluded.extend(
+                excl.correlated
+                    .iter()
+                    .filter(|l| l.category == category)
+                    .map(|l| l.variant),
+            );
+        }
+    }
+    excluded
+}
+
 /// Returns HandleResult
 ///
 /// picks a random winner out of a weight table
@@ -2107,6 +3249,141 @@ pub struct BackCache {
     pub index: u8,
 }
 
+/// the layer configuration and caches used to roll a batch of new genes
+struct GeneRoller {
+    /// the roll config
+    roll: RollConfig,
+    /// list of traits that have multiple layers
+    depends: Vec<StoredDependencies>,
+    /// list of variants that hide other variants
+    hiders: Vec<StoredDependencies>,
+    /// list of variants that can not appear with other variants
+    exclusions: Vec<StoredDependencies>,
+    /// the archetype categories and variants
+    archetype: StoredArchetype,
+    /// list of archetype category indices
+    archetype_idxs: Vec<u8>,
+    /// every category index in the order they are rolled
+    order: Vec<u8>,
+    /// starting seed for each gene with the skipped categories set to None
+    gene_seed: Vec<u8>,
+    /// None trait variants that have already been retrieved
+    none_cache: Vec<StoredLayerId>,
+    /// Categories that have already been retrieved
+    cat_cache: Vec<RefCache<Category>>,
+    /// skull variants that have already been retrieved
+    skull_cache: Vec<RefCache<Variant>>,
+    /// backgrounds that have already been retrieved
+    back_cache: Vec<BackCache>,
+    /// chin variants that have already been retrieved
+    chin_cache: Vec<BackCache>,
+    /// uniqueness masks of the genes rolled so far
+    uniques: Vec<Vec<u8>>,
+    /// number of failed uniqueness checks
+    rerolls: u32,
+    /// number of times a gene had to start over
+    restarts: u32,
+}
+
+impl GeneRoller {
+    /// Returns StdResult<GeneRoller> loaded with the current layer configuration
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - a reference to the contract's storage
+    fn new<S: ReadonlyStorage>(storage: &S) -> StdResult<Self> {
+        let roll: RollConfig = load(storage, ROLL_CONF_KEY)?;
+        let depends: Vec<StoredDependencies> =
+            may_load(storage, DEPENDENCIES_KEY)?.unwrap_or_else(Vec::new);
+        let hiders: Vec<StoredDependencies> =
+            may_load(storage, HIDERS_KEY)?.unwrap_or_else(Vec::new);
+        let exclusions: Vec<StoredDependencies> =
+            may_load(storage, EXCLUSIONS_KEY)?.unwrap_or_else(Vec::new);
+        let archetype = StoredArchetype::effective(storage, &roll)?
+            .ok_or_else(|| StdError::generic_err("The archetype categories have not been set"))?;
+        let mut none_cache: Vec<StoredLayerId> = Vec::new();
+        // any layers being skipped should be set to None
+        let mut gene_seed: Vec<u8> = vec![255; roll.cat_cnt as usize];
+        for skip_cat in roll.skip.iter() {
+            let none_idx = use_none_cache(storage, *skip_cat, &mut none_cache)?;
+            gene_seed[*skip_cat as usize] = none_idx;
+        }
+        let archetype_idxs = vec![archetype.skull, archetype.jaw, archetype.eye_type];
+        let order = archetype.roll_order(roll.cat_cnt);
+        Ok(GeneRoller {
+            roll,
+            depends,
+            hiders,
+            exclusions,
+            archetype,
+            archetype_idxs,
+            order,
+            gene_seed,
+            none_cache,
+            cat_cache: Vec::new(),
+            skull_cache: Vec::new(),
+            back_cache: Vec::new(),
+            chin_cache: Vec::new(),
+            uniques: Vec::new(),
+            rerolls: 0,
+            restarts: 0,
+        })
+    }
+
+    /// Returns StdResult<GeneInfo> of a new gene with the specified background that is
+    /// unique from the existing genes and the others rolled by this GeneRoller
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - a reference to the contract's storage
+    /// * `rng` - a mutable reference to the Prng
+    /// * `background` - name of the background variant
+    fn roll_gene<S: ReadonlyStorage>(
+        &mut self,
+        storage: &S,
+        rng: &mut Prng,
+        background: &str,
+    ) -> StdResult<GeneInfo> {
+        // background is always the first layer
+        let background_map =
+            ReadonlyPrefixedStorage::multilevel(&[PREFIX_VARIANT_MAP, &0u8.to_le_bytes()], storage);
+        self.gene_seed[0] = use_back_cache(&background_map, background, &mut self.back_cache)?;
+        let mut restarts = 0u32;
+        loop {
+            let (reroll, current_image, genetic_image, unique_check) = new_gene_impl(
+                storage,
+                rng,
+                &self.roll,
+                &self.depends,
+                &self.hiders,
+                &self.exclusions,
+                &self.archetype,
+                &mut self.none_cache,
+                &mut self.cat_cache,
+                &mut self.skull_cache,
+                &mut self.chin_cache,
+                &self.gene_seed,
+                &mut self.uniques,
+                &self.archetype_idxs,
+                &self.order,
+                &mut self.rerolls,
+            )?;
+            if !reroll {
+                return Ok(GeneInfo {
+                    current_image,
+                    genetic_image,
+                    unique_check,
+                });
+            }
+            self.restarts += 1;
+            restarts += 1;
+            if restarts > MAX_RESTARTS {
+                return Err(too_many_restarts());
+            }
+        }
+    }
+}
+
 /// Returns StdResult<(bool, Vec<u8>, Vec<u8>, Vec<u8>)>
 ///
 /// creates a random NFT, and returns the revealed image, complete genetic image, and
@@ -2120,17 +3397,17 @@ pub struct BackCache {
 /// * `roll` - a reference to the RollConfig
 /// * `depends` - list of traits that have multiple layers
 /// * `hiders` - list of variants that hide other variants
-/// * `eye_type_idx` - Eye Type category index
-/// * `chin_idx` - Chin category index
-/// * `skull_idx` - Skull category index
+/// * `exclusions` - list of variants that can not appear with other variants
+/// * `archetype` - a reference to the archetype categories and variants
 /// * `none_cache` - list of None trait variants that have already been retrieved
 /// * `cat_cache` - list of Categories that have already been retrieved
 /// * `skull_cache` - list of skull variants that have already been retrieved
-/// * `eye_type_cache` - list of eye type variants that have already been retrieved
 /// * `chin_cache` - list of chin variants that have already been retrieved
 /// * `gene_seed` - starting seed for the gene including skipped categories and background
 /// * `uniques` - list of uniqueness masks for the current batch of new genes
 /// * `archetype_idxs` - list of archetype category indices
+/// * `order` - every category index in the order they are rolled
+/// * `rerolls` - a mutable reference to the number of failed uniqueness checks
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn new_gene_impl<S: ReadonlyStorage>(
     storage: &S,
@@ -2138,24 +3415,23 @@ fn new_gene_impl<S: ReadonlyStorage>(
     roll: &RollConfig,
     depends: &[StoredDependencies],
     hiders: &[StoredDependencies],
-    eye_type_idx: u8,
-    chin_idx: u8,
-    skull_idx: u8,
+    exclusions: &[StoredDependencies],
+    archetype: &StoredArchetype,
     none_cache: &mut Vec<StoredLayerId>,
     cat_cache: &mut Vec<RefCache<Category>>,
     skull_cache: &mut Vec<RefCache<Variant>>,
-    eye_type_cache: &mut Vec<RefCache<Variant>>,
     chin_cache: &mut Vec<BackCache>,
     gene_seed: &[u8],
     uniques: &mut Vec<Vec<u8>>,
     archetype_idxs: &[u8],
+    order: &[u8],
+    rerolls: &mut u32,
 ) -> StdResult<(bool, Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let skull_idx = archetype.skull;
+    let chin_idx = archetype.jaw;
+    let eye_type_idx = archetype.eye_type;
     // define some storages
     let cat_store = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY, storage);
-    let eye_type_var_store = ReadonlyPrefixedStorage::multilevel(
-        &[PREFIX_VARIANT, &eye_type_idx.to_le_bytes()],
-        storage,
-    );
     let skull_var_store =
         ReadonlyPrefixedStorage::multilevel(&[PREFIX_VARIANT, &skull_idx.to_le_bytes()], storage);
     let chin_var_map = ReadonlyPrefixedStorage::multilevel(
@@ -2178,14 +3454,25 @@ fn new_gene_impl<S: ReadonlyStorage>(
         .get(cat_cache_idx)
         .ok_or_else(|| StdError::generic_err("Skull_cat index out of bounds"))?
         .item;
-    let skull = draw_variant(rng, &skull_cat.normal_weights);
+    // archetype traits must also respect the exclusions of the background and skipped layers
+    let skull = if let Some(s) = draw_allowed(
+        rng,
+        &skull_cat.normal_weights,
+        &excluded_variants(skull_idx, &genetic_image, exclusions),
+    ) {
+        s
+    } else {
+        return Ok((true, Vec::new(), Vec::new(), Vec::new()));
+    };
     // archetype traits are revealed immediately
     current_image[skull_idx as usize] = skull;
     genetic_image[skull_idx as usize] = skull;
     skipping[skull_idx as usize] = true;
-    // if jawless, set chin to None
+    // if jawless, use the jawless chin variant
     let chin_var = if is_jawless {
-        use_none_cache(storage, chin_idx, none_cache)?
+        archetype.jawless.ok_or_else(|| {
+            StdError::generic_err("The archetype does not have a jaw variant for jawless skulls")
+        })?
     } else {
         // otherwise use the same chin as the skull type
         let skull_cache_idx = use_ref_cache(&skull_var_store, skull, skull_cache)?;
@@ -2195,6 +3482,9 @@ fn new_gene_impl<S: ReadonlyStorage>(
             .item;
         use_back_cache(&chin_var_map, &skull_var.name, chin_cache)?
     };
+    if excluded_variants(chin_idx, &genetic_image, exclusions).contains(&chin_var) {
+        return Ok((true, Vec::new(), Vec::new(), Vec::new()));
+    }
     // archetype traits are revealed immediately
     current_image[chin_idx as usize] = chin_var;
     genetic_image[chin_idx as usize] = chin_var;
@@ -2205,24 +3495,59 @@ fn new_gene_impl<S: ReadonlyStorage>(
         .get(cat_cache_idx)
         .ok_or_else(|| StdError::generic_err("Eye type cat index out of bounds"))?
         .item;
-    let et = draw_variant(rng, &et_cat.normal_weights);
-    let eye_cache_idx = use_ref_cache(&eye_type_var_store, et, eye_type_cache)?;
-    let et_var: &Variant = &eye_type_cache
-        .get(eye_cache_idx)
-        .ok_or_else(|| StdError::generic_err("Eye type cache index out of bounds"))?
-        .item;
-    let is_cyclops = et_var.display == *"Cyclops";
+    let et = if let Some(e) = draw_allowed(
+        rng,
+        &et_cat.normal_weights,
+        &excluded_variants(eye_type_idx, &genetic_image, exclusions),
+    ) {
+        e
+    } else {
+        return Ok((true, Vec::new(), Vec::new(), Vec::new()));
+    };
+    let is_cyclops = archetype.cyclops == Some(et);
     // archetype traits are revealed immediately
     current_image[eye_type_idx as usize] = et;
     genetic_image[eye_type_idx as usize] = et;
     skipping[eye_type_idx as usize] = true;
 
-    let mut idx = 1u8;
+    // roll position of each category
+    let mut position: Vec<u8> = vec![0; roll.cat_cnt as usize];
+    for (pos, cat_idx) in order.iter().enumerate() {
+        position[*cat_idx as usize] = pos as u8;
+    }
+    // find the roll position of the last category with a conditional keyed by each category,
+    // so that rerolling a condition does not check uniqueness until its conditioned categories
+    // are rolled again
+    let mut last_conditioned: Vec<u8> = vec![0; roll.cat_cnt as usize];
+    for (pos, cond_idx) in order.iter().enumerate().skip(1) {
+        let cond_idx = *cond_idx;
+        if skipping[cond_idx as usize] {
+            continue;
+        }
+        let cat_cache_idx = use_ref_cache(&cat_store, cond_idx, cat_cache)?;
+        let cat: &Category = &cat_cache
+            .get(cat_cache_idx)
+            .ok_or_else(|| StdError::generic_err("CatCache index out of bounds"))?
+            .item;
+        for cond in cat.conditionals.iter() {
+            if let Some(last) = last_conditioned.get_mut(cond.condition.category as usize) {
+                if *last < pos as u8 {
+                    *last = pos as u8;
+                }
+            }
+        }
+    }
+    let mut check_after = 0u8;
+    let mut pos = 1usize;
     let mut first_pass = true;
     // roll the rest
     loop {
         // if already rolled every trait
-        if idx >= roll.cat_cnt {
+        if pos >= order.len() {
+            // layers set by forced variants or dependencies may still conflict
+            if violates_exclusions(&genetic_image, exclusions) {
+                return Ok((true, Vec::new(), Vec::new(), Vec::new()));
+            }
             if let Some(unique_check) = check_unique(
                 storage,
                 &genetic_image,
@@ -2236,15 +3561,17 @@ fn new_gene_impl<S: ReadonlyStorage>(
             )? {
                 return Ok((false, current_image, genetic_image, unique_check));
             }
+            *rerolls += 1;
             // if skipping everything, return to try rerolling everything
             if skipping.iter().all(|b| *b) {
                 return Ok((true, Vec::new(), Vec::new(), Vec::new()));
             }
             // start rerolling
             first_pass = false;
-            idx = 1u8;
+            pos = 1;
             continue;
         }
+        let idx = order[pos];
         if !*skipping
             .get(idx as usize)
             .ok_or_else(|| StdError::generic_err("Skipping index out of bounds"))?
@@ -2254,8 +3581,18 @@ fn new_gene_impl<S: ReadonlyStorage>(
                 .get(cat_cache_idx)
                 .ok_or_else(|| StdError::generic_err("CatCache index out of bounds"))?
                 .item;
+            // find the first conditional whose condition has been rolled
+            let conditional = cat.conditionals.iter().find(|c| {
+                position
+                    .get(c.condition.category as usize)
+                    .map_or(false, |p| (*p as usize) < pos)
+                    && genetic_image.get(c.condition.category as usize)
+                        == Some(&c.condition.variant)
+            });
             // grab the right weight table
-            let weights = if let Some(jawless) = cat.jawless_weights.as_ref() {
+            let weights = if let Some(cond_wgts) = conditional.and_then(|c| c.weights.as_ref()) {
+                cond_wgts
+            } else if let Some(jawless) = cat.jawless_weights.as_ref() {
                 if is_jawless {
                     jawless
                 } else {
@@ -2270,24 +3607,44 @@ fn new_gene_impl<S: ReadonlyStorage>(
             } else {
                 &cat.normal_weights
             };
-            // see if there is a forced variant
-            let forced = if cat.forced_jawless.is_some() && is_jawless {
-                cat.forced_jawless.as_ref()
+            // see if there is a forced variant, and if it can be locked in and revealed
+            let (forced, lock) = if let Some(cond) = conditional.filter(|c| c.forced.is_some()) {
+                // a conditional forced variant can only be locked in if its condition will not
+                // be rerolled, and only revealed if its condition is revealed
+                let cond_idx = cond.condition.category as usize;
+                let lock = skipping.get(cond_idx).copied().unwrap_or(false);
+                let reveal = lock && current_image.get(cond_idx).map_or(false, |v| *v != 255);
+                (cond.forced, if lock { Some(reveal) } else { None })
+            } else if cat.forced_jawless.is_some() && is_jawless {
+                (cat.forced_jawless, Some(true))
             } else if cat.forced_cyclops.is_some() && is_cyclops {
-                cat.forced_cyclops.as_ref()
+                (cat.forced_cyclops, Some(true))
             } else {
-                None
+                (None, None)
             };
-            // forced variants are revealed immediately
+            // don't use any variant that can not appear with the ones already chosen
+            let excluded = excluded_variants(idx, &genetic_image, exclusions);
             let mut reveal_it: Option<&mut Vec<u8>> = None;
             let winner = if let Some(f) = forced {
-                current_image[idx as usize] = *f;
-                reveal_it = Some(&mut current_image);
-                // don't attempt to reroll a forced variant
-                skipping[idx as usize] = true;
-                *f
+                // if a forced variant is excluded, start over
+                if excluded.contains(&f) {
+                    return Ok((true, Vec::new(), Vec::new(), Vec::new()));
+                }
+                if let Some(reveal) = lock {
+                    // forced variants are revealed immediately if their condition is
+                    if reveal {
+                        current_image[idx as usize] = f;
+                        reveal_it = Some(&mut current_image);
+                    }
+                    // don't attempt to reroll a forced variant
+                    skipping[idx as usize] = true;
+                }
+                f
+            } else if let Some(w) = draw_allowed(rng, weights, &excluded) {
+                w
             } else {
-                draw_variant(rng, weights)
+                // if every variant is excluded, start over
+                return Ok((true, Vec::new(), Vec::new(), Vec::new()));
             };
             genetic_image[idx as usize] = winner;
             // add additional layers for this trait if necessary
@@ -2299,8 +3656,26 @@ fn new_gene_impl<S: ReadonlyStorage>(
                 reveal_it,
                 &mut skipping,
             );
+            // if any added layer can not appear with the rest, start over
+            if depends
+                .iter()
+                .find(|d| d.id.category == idx && d.id.variant == winner)
+                .map_or(false, |d| {
+                    d.correlated.iter().any(|l| {
+                        excluded_variants(l.category, &genetic_image, exclusions)
+                            .contains(&l.variant)
+                    })
+                })
+            {
+                return Ok((true, Vec::new(), Vec::new(), Vec::new()));
+            }
+            // wait for any categories conditioned on this one to be rerolled as well
+            check_after = check_after.max(last_conditioned[idx as usize]);
             // if already rolled every trait, see if you have a unique gene
-            if !first_pass {
+            if !first_pass && pos >= check_after as usize {
+                if violates_exclusions(&genetic_image, exclusions) {
+                    return Ok((true, Vec::new(), Vec::new(), Vec::new()));
+                }
                 if let Some(unique_check) = check_unique(
                     storage,
                     &genetic_image,
@@ -2314,9 +3689,10 @@ fn new_gene_impl<S: ReadonlyStorage>(
                 )? {
                     return Ok((false, current_image, genetic_image, unique_check));
                 }
+                *rerolls += 1;
             }
         }
-        idx += 1;
+        pos += 1;
     }
 }
 
@@ -2402,12 +3778,38 @@ fn displ_variant<S: ReadonlyStorage>(
     };
     let var: Variant = may_load(&var_store, &id.variant.to_le_bytes())?
         .ok_or_else(|| StdError::generic_err("Variant storage is corrupt"))?;
+    let cat_store = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY, storage);
+    let render_before = var
+        .render_before
+        .map(|u| {
+            may_load::<Category, _>(&cat_store, &u.to_le_bytes())?
+                .ok_or_else(|| StdError::generic_err("Category storage is corrupt"))
+                .map(|c| c.name)
+        })
+        .transpose()?;
+    let recolor = var
+        .recolor
+        .as_ref()
+        .map(|r| r.to_display(storage, id.category))
+        .transpose()?;
+    // a recolor displays the svg it draws
+    let (svg, animation) = if svgs {
+        (
+            variant_svg(storage, id.category, &var)?,
+            var.animation
+                .as_ref()
+                .map(|a| expand_fragments(storage, a))
+                .transpose()?,
+        )
+    } else {
+        (None, None)
+    };
     let var_inf = VariantInfoPlus {
         index: id.variant,
         variant_info: VariantInfo {
             name: var.name,
             display_name: var.display,
-            svg: var.svg.filter(|_| svgs),
+            svg,
             normal_weight: *cat
                 .normal_weights
                 .get(id.variant as usize)
@@ -2430,6 +3832,9 @@ fn displ_variant<S: ReadonlyStorage>(
                         .ok_or_else(|| StdError::generic_err("Cyclops weight table is corrupt"))
                 })
                 .transpose()?,
+            render_before,
+            recolor,
+            animation,
         },
         includes,
         hides_at_launch,
@@ -2522,3 +3927,293 @@ fn use_ref_cache<S: ReadonlyStorage, T: DeserializeOwned>(
         Ok(ref_cache.len() - 1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    /// a category's name, variants as (name, display) pairs, and weight tables
+    struct TestCategory {
+        name: &'static str,
+        variants: Vec<(&'static str, &'static str)>,
+        normal_weights: Vec<u16>,
+        jawless_weights: Option<Vec<u16>>,
+        cyclops_weights: Option<Vec<u16>>,
+    }
+
+    fn category(name: &'static str, variants: &[&'static str], weights: &[u16]) -> TestCategory {
+        TestCategory {
+            name,
+            variants: variants.iter().map(|v| (*v, *v)).collect(),
+            normal_weights: weights.to_vec(),
+            jawless_weights: None,
+            cyclops_weights: None,
+        }
+    }
+
+    /// the categories the original hard-coded rolling looked up by name
+    fn legacy_categories() -> Vec<TestCategory> {
+        let mut eye_type = category("Eye Type", &["Round", "Narrow"], &[3, 2, 1]);
+        eye_type.variants.push(("One Eye", "Cyclops"));
+        let mut eyes = category("Eyes", &["Blue", "Green", "Red", "Gold"], &[1, 1, 1, 1]);
+        eyes.cyclops_weights = Some(vec![1, 1, 0, 1]);
+        let mut mouth = category("Mouth", &["Smile", "Frown", "Teeth"], &[1, 1, 1]);
+        mouth.jawless_weights = Some(vec![1, 0, 0]);
+        vec![
+            category("Background", &["Cyan", "Red"], &[1, 1]),
+            category("Skull", &["Pale", "Gray"], &[1, 1]),
+            category("Jaw Type", &["None", "Pale", "Gray"], &[1, 1, 1]),
+            eye_type,
+            category(
+                "Hair",
+                &["None", "Mohawk", "Bun", "Spikes", "Long"],
+                &[1, 2, 2, 2, 2],
+            ),
+            eyes,
+            mouth,
+            category("Nose", &["Hole", "Point", "Flat"], &[1, 1, 1]),
+        ]
+    }
+
+    fn store_config(storage: &mut MockStorage, categories: &[TestCategory], jaw_weights: &[u16]) {
+        for (idx, cat) in categories.iter().enumerate() {
+            let cat_key = (idx as u8).to_le_bytes();
+            let mut cat_store = PrefixedStorage::new(PREFIX_CATEGORY, storage);
+            save(
+                &mut cat_store,
+                &cat_key,
+                &Category {
+                    name: cat.name.to_string(),
+                    forced_cyclops: None,
+                    forced_jawless: None,
+                    normal_weights: cat.normal_weights.clone(),
+                    jawless_weights: cat.jawless_weights.clone(),
+                    cyclops_weights: cat.cyclops_weights.clone(),
+                    conditionals: Vec::new(),
+                },
+            )
+            .unwrap();
+            let mut cat_map = PrefixedStorage::new(PREFIX_CATEGORY_MAP, storage);
+            save(&mut cat_map, cat.name.as_bytes(), &(idx as u8)).unwrap();
+            for (var_idx, (name, display)) in cat.variants.iter().enumerate() {
+                let mut var_store =
+                    PrefixedStorage::multilevel(&[PREFIX_VARIANT, &cat_key], storage);
+                save(
+                    &mut var_store,
+                    &(var_idx as u8).to_le_bytes(),
+                    &Variant {
+                        name: name.to_string(),
+                        svg: None,
+                        display: display.to_string(),
+                        render_before: None,
+                        recolor: None,
+                        animation: None,
+                    },
+                )
+                .unwrap();
+                let mut var_map =
+                    PrefixedStorage::multilevel(&[PREFIX_VARIANT_MAP, &cat_key], storage);
+                save(&mut var_map, name.as_bytes(), &(var_idx as u8)).unwrap();
+            }
+        }
+        let roll = RollConfig {
+            cat_cnt: categories.len() as u8,
+            skip: Vec::new(),
+            jaw_weights: jaw_weights.to_vec(),
+            archetype: None,
+            render_order: (0..categories.len() as u8).collect(),
+        };
+        save(storage, ROLL_CONF_KEY, &roll).unwrap();
+    }
+
+    /// the gene rolling from before the archetype was configurable, for configs without
+    /// dependencies, hidden variants, forced variants, or conditionals
+    fn baseline_gene(
+        storage: &MockStorage,
+        rng: &mut Prng,
+        background: &str,
+        uniques: &mut Vec<Vec<u8>>,
+    ) -> GeneInfo {
+        let roll: RollConfig = load(storage, ROLL_CONF_KEY).unwrap();
+        let cat_map = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY_MAP, storage);
+        let cat_idx = |name: &str| -> u8 { may_load(&cat_map, name.as_bytes()).unwrap().unwrap() };
+        let (skull_idx, chin_idx, eye_type_idx) =
+            (cat_idx("Skull"), cat_idx("Jaw Type"), cat_idx("Eye Type"));
+        let archetype_idxs = vec![skull_idx, chin_idx, eye_type_idx];
+        let cat_store = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY, storage);
+        let load_cat = |idx: u8| -> Category { load(&cat_store, &idx.to_le_bytes()).unwrap() };
+        let load_var = |cat: u8, var: u8| -> Variant {
+            let var_store =
+                ReadonlyPrefixedStorage::multilevel(&[PREFIX_VARIANT, &cat.to_le_bytes()], storage);
+            load(&var_store, &var.to_le_bytes()).unwrap()
+        };
+        let var_idx = |cat: u8, name: &str| -> u8 {
+            let var_map = ReadonlyPrefixedStorage::multilevel(
+                &[PREFIX_VARIANT_MAP, &cat.to_le_bytes()],
+                storage,
+            );
+            may_load(&var_map, name.as_bytes()).unwrap().unwrap()
+        };
+        let mut none_cache: Vec<StoredLayerId> = Vec::new();
+        let mut gene_seed: Vec<u8> = vec![255; roll.cat_cnt as usize];
+        gene_seed[0] = var_idx(0, background);
+        'restart: loop {
+            let mut current_image: Vec<u8> = vec![255; roll.cat_cnt as usize];
+            let mut genetic_image: Vec<u8> = gene_seed.clone();
+            let mut skipping: Vec<bool> = gene_seed.iter().map(|u| *u != 255u8).collect();
+            current_image[0] = genetic_image[0];
+            let is_jawless = draw_variant(rng, &roll.jaw_weights) == 1;
+            let skull = draw_variant(rng, &load_cat(skull_idx).normal_weights);
+            current_image[skull_idx as usize] = skull;
+            genetic_image[skull_idx as usize] = skull;
+            skipping[skull_idx as usize] = true;
+            let chin_var = if is_jawless {
+                var_idx(chin_idx, "None")
+            } else {
+                var_idx(chin_idx, &load_var(skull_idx, skull).name)
+            };
+            current_image[chin_idx as usize] = chin_var;
+            genetic_image[chin_idx as usize] = chin_var;
+            skipping[chin_idx as usize] = true;
+            let et = draw_variant(rng, &load_cat(eye_type_idx).normal_weights);
+            let is_cyclops = load_var(eye_type_idx, et).display == "Cyclops";
+            current_image[eye_type_idx as usize] = et;
+            genetic_image[eye_type_idx as usize] = et;
+            skipping[eye_type_idx as usize] = true;
+            let mut first_pass = true;
+            let mut idx = 1u8;
+            loop {
+                if idx >= roll.cat_cnt {
+                    if let Some(unique_check) = check_unique(
+                        storage,
+                        &genetic_image,
+                        &[],
+                        roll.cat_cnt,
+                        &mut none_cache,
+                        is_cyclops,
+                        is_jawless,
+                        &archetype_idxs,
+                        uniques,
+                    )
+                    .unwrap()
+                    {
+                        return GeneInfo {
+                            current_image,
+                            genetic_image,
+                            unique_check,
+                        };
+                    }
+                    if skipping.iter().all(|b| *b) {
+                        continue 'restart;
+                    }
+                    first_pass = false;
+                    idx = 1;
+                    continue;
+                }
+                if !skipping[idx as usize] {
+                    let cat = load_cat(idx);
+                    let weights = match (&cat.jawless_weights, &cat.cyclops_weights) {
+                        (Some(jawless), _) if is_jawless => jawless.clone(),
+                        (Some(_), _) => cat.normal_weights.clone(),
+                        (None, Some(cyclops)) if is_cyclops => cyclops.clone(),
+                        _ => cat.normal_weights.clone(),
+                    };
+                    genetic_image[idx as usize] = draw_variant(rng, &weights);
+                    if !first_pass {
+                        if let Some(unique_check) = check_unique(
+                            storage,
+                            &genetic_image,
+                            &[],
+                            roll.cat_cnt,
+                            &mut none_cache,
+                            is_cyclops,
+                            is_jawless,
+                            &archetype_idxs,
+                            uniques,
+                        )
+                        .unwrap()
+                        {
+                            return GeneInfo {
+                                current_image,
+                                genetic_image,
+                                unique_check,
+                            };
+                        }
+                    }
+                }
+                idx += 1;
+            }
+        }
+    }
+
+    /// rolls the same genes with the archetype fallback and with the baseline rolling
+    fn compare_to_baseline(storage: &MockStorage) {
+        let backgrounds = ["Cyan", "Red"];
+        let mut roller = GeneRoller::new(storage).unwrap();
+        let mut rng = Prng::new(b"seed", b"entropy");
+        let mut baseline_rng = Prng::new(b"seed", b"entropy");
+        let mut baseline_uniques: Vec<Vec<u8>> = Vec::new();
+        for i in 0..40 {
+            let background = backgrounds[i % 2];
+            let gene = roller.roll_gene(storage, &mut rng, background).unwrap();
+            let expected = baseline_gene(
+                storage,
+                &mut baseline_rng,
+                background,
+                &mut baseline_uniques,
+            );
+            assert_eq!(gene, expected, "gene {} differs from the baseline", i);
+        }
+    }
+
+    #[test]
+    fn test_archetype_fallback_matches_baseline() {
+        let mut storage = MockStorage::new();
+        store_config(&mut storage, &legacy_categories(), &[3, 1]);
+        let roll: RollConfig = load(&storage, ROLL_CONF_KEY).unwrap();
+
+        // the fallback finds the categories and variants the baseline looked up by name
+        let archetype = StoredArchetype::effective(&storage, &roll)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            archetype,
+            StoredArchetype {
+                skull: 1,
+                jaw: 2,
+                eye_type: 3,
+                cyclops: Some(2),
+                jawless: Some(0),
+                placeholder: Some(4),
+                roll_first: Vec::new(),
+            }
+        );
+        // and rolls the remaining categories by index like the baseline
+        assert_eq!(
+            archetype.roll_order(roll.cat_cnt),
+            (0..8).collect::<Vec<u8>>()
+        );
+
+        compare_to_baseline(&storage);
+    }
+
+    #[test]
+    fn test_archetype_fallback_without_cyclops_or_jawless_matches_baseline() {
+        let mut storage = MockStorage::new();
+        let mut categories = legacy_categories();
+        categories[2] = category("Jaw Type", &["Pale", "Gray"], &[1, 1]);
+        categories[3] = category("Eye Type", &["Round", "Narrow", "Wide"], &[3, 2, 1]);
+        // nothing can be jawless without a None jaw
+        store_config(&mut storage, &categories, &[1, 0]);
+        let roll: RollConfig = load(&storage, ROLL_CONF_KEY).unwrap();
+
+        let archetype = StoredArchetype::effective(&storage, &roll)
+            .unwrap()
+            .unwrap();
+        assert_eq!(archetype.cyclops, None);
+        assert_eq!(archetype.jawless, None);
+
+        compare_to_baseline(&storage);
+    }
+}
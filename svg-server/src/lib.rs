@@ -3,9 +3,11 @@ pub mod contract;
 mod metadata;
 pub mod msg;
 mod rand;
+mod render;
 pub mod state;
 mod storage;
 mod utils;
+mod validation;
 mod viewing_key;
 
 #[cfg(target_arch = "wasm32")]
@@ -1,7 +1,9 @@
 #![allow(clippy::large_enum_variant)]
 use crate::metadata::Metadata;
+use crate::render::{hex_color, parse_hex_color, FRAGMENT_REF};
 use crate::state::{
-    Category, Variant, PREFIX_CATEGORY, PREFIX_CATEGORY_MAP, PREFIX_VARIANT, PREFIX_VARIANT_MAP,
+    Category, RollConfig, Variant, PREFIX_CATEGORY, PREFIX_CATEGORY_MAP, PREFIX_VARIANT,
+    PREFIX_VARIANT_MAP,
 };
 use crate::storage::may_load;
 use cosmwasm_std::{HumanAddr, ReadonlyStorage, StdError, StdResult};
@@ -67,7 +69,8 @@ pub enum HandleMsg {
     AddCategories { categories: Vec<CategoryInfo> },
     /// add new trait variants to existing categories
     AddVariants { variants: Vec<CategoryInfo> },
-    /// change the name, forced variants, or weight tables for an existing trait category
+    /// change the name, forced variants, weight tables, or conditionals for an existing trait
+    /// category
     ModifyCategory {
         /// name of the trait category to modify
         name: String,
@@ -77,6 +80,9 @@ pub enum HandleMsg {
         forced_variants: Option<ForcedVariants>,
         /// optional new weight tables for the category
         weights: Option<Weights>,
+        /// optional new list of conditional weight tables and forced variants.  This replaces
+        /// the existing list, so an empty list removes all conditionals
+        conditionals: Option<Vec<Conditional>>,
     },
     /// modify existing trait variants
     ModifyVariants { modifications: Vec<VariantModInfo> },
@@ -87,8 +93,34 @@ pub enum HandleMsg {
         /// common private metadata
         private_metadata: Option<Metadata>,
     },
-    /// Sets the layer categories to skip when rolling and the weightings for jawed vs
-    /// jawless skulls
+    /// set the svg canvas that wraps every image
+    SetCanvas {
+        /// the new svg canvas
+        canvas: SvgCanvas,
+    },
+    /// add or replace named svg fragments that variants can reference with `{frag:name}`
+    /// or `{frag:name:palette}`
+    SetFragments {
+        /// fragments to add or replace
+        fragments: Vec<Fragment>,
+    },
+    /// remove named svg fragments
+    RemoveFragments {
+        /// names of the fragments to remove
+        names: Vec<String>,
+    },
+    /// add or replace named color palettes used when expanding fragments
+    SetPalettes {
+        /// palettes to add or replace
+        palettes: Vec<Palette>,
+    },
+    /// remove named color palettes
+    RemovePalettes {
+        /// names of the palettes to remove
+        names: Vec<String>,
+    },
+    /// Sets the layer categories to skip when rolling, the weightings for jawed vs
+    /// jawless skulls, and the archetype categories
     SetRollConfig {
         /// names of the layer categories to skip when rolling
         skip: Option<Vec<String>>,
@@ -96,6 +128,13 @@ pub enum HandleMsg {
         jaw_weight: Option<u16>,
         /// weight for jawless skulls
         jawless_weight: Option<u16>,
+        /// the categories and variants that determine a skull's archetype
+        archetype: Option<Archetype>,
+    },
+    /// sets the order the layer categories are drawn in
+    SetRenderOrder {
+        /// names of every layer category in the order they should be drawn
+        categories: Vec<String>,
     },
     /// add dependencies for traits that have multiple layers
     AddDependencies {
@@ -127,8 +166,26 @@ pub enum HandleMsg {
         /// hiders to modify
         hiders: Vec<Dependencies>,
     },
-    /// allow a minter to add genes to prevent future duplicates
-    AddGenes { genes: Vec<Vec<u8>> },
+    /// add trait variants that can not appear with other trait variants
+    AddExclusions {
+        /// new exclusions to add
+        exclusions: Vec<Dependencies>,
+    },
+    /// remove trait variants that can not appear with other trait variants
+    RemoveExclusions {
+        /// exclusions to remove
+        exclusions: Vec<Dependencies>,
+    },
+    /// allow a minter to add genes to prevent future duplicates and count their traits
+    AddGenes { genes: Vec<GeneInfo> },
+    /// count the traits of genes minted before the trait counts were kept.  Rarity scores
+    /// are not displayed until the backfill is complete
+    BackfillTraitCounts {
+        /// genetic images of previously minted genes
+        genetic_images: Vec<Vec<u8>>,
+        /// true if these are the last of the previously minted genes
+        complete: bool,
+    },
     /// disallow the use of a permit
     RevokePermit {
         /// name of the permit that is no longer valid
@@ -170,8 +227,20 @@ pub enum HandleAnswer {
     ModifyVariants { status: String },
     /// response from setting common metadata
     SetMetadata { metadata: CommonMetadata },
+    /// response from setting the svg canvas
+    SetCanvas { canvas: SvgCanvas },
+    /// response from adding or replacing svg fragments
+    SetFragments { status: String },
+    /// response from removing svg fragments
+    RemoveFragments { status: String },
+    /// response from adding or replacing color palettes
+    SetPalettes { status: String },
+    /// response from removing color palettes
+    RemovePalettes { status: String },
     /// response from setting the roll config
     SetRollConfig { status: String },
+    /// response from setting the render order
+    SetRenderOrder { status: String },
     /// response from adding dependencies
     AddDependencies { status: String },
     /// response from removing dependencies
@@ -184,8 +253,19 @@ pub enum HandleAnswer {
     RemoveHiders { status: String },
     /// response from modifying trait hiders
     ModifyHiders { status: String },
+    /// response from adding exclusions
+    AddExclusions { status: String },
+    /// response from removing exclusions
+    RemoveExclusions { status: String },
     /// response from revoking a permit
     RevokePermit { status: String },
+    /// response from backfilling the trait counts
+    BackfillTraitCounts {
+        /// number of genes counted
+        total: u32,
+        /// true if the backfill is complete
+        complete: bool,
+    },
 }
 
 /// Queries
@@ -240,6 +320,32 @@ pub enum QueryMsg {
         /// are provided, the viewer will be ignored
         permit: Option<Permit>,
     },
+    /// displays the svg canvas that wraps every image
+    Canvas {
+        /// optional address and viewing key of an admin, minter, or viewer
+        viewer: Option<ViewerInfo>,
+        /// optional permit used to verify identity.  If both viewer and permit
+        /// are provided, the viewer will be ignored
+        permit: Option<Permit>,
+    },
+    /// displays named svg fragments
+    Fragments {
+        /// optional address and viewing key of an admin
+        viewer: Option<ViewerInfo>,
+        /// optional permit used to verify admin identity.  If both viewer and permit
+        /// are provided, the viewer will be ignored
+        permit: Option<Permit>,
+        /// names of the fragments to display
+        names: Vec<String>,
+    },
+    /// displays the color palettes
+    Palettes {
+        /// optional address and viewing key of an admin
+        viewer: Option<ViewerInfo>,
+        /// optional permit used to verify admin identity.  If both viewer and permit
+        /// are provided, the viewer will be ignored
+        permit: Option<Permit>,
+    },
     /// displays the layer categories that get skipped during rolls and the weights of
     /// jawed vs jawless skulls
     RollConfig {
@@ -273,6 +379,27 @@ pub enum QueryMsg {
         /// max number of hiders to display
         limit: Option<u16>,
     },
+    /// displays the trait variants that can not appear with other trait variants
+    Exclusions {
+        /// optional address and viewing key of an admin
+        viewer: Option<ViewerInfo>,
+        /// optional permit used to verify admin identity.  If both viewer and permit
+        /// are provided, the viewer will be ignored
+        permit: Option<Permit>,
+        /// optional exclusion index to start at
+        start_at: Option<u16>,
+        /// max number of exclusions to display
+        limit: Option<u16>,
+    },
+    /// checks the categories, variants, weight tables, roll config, dependencies, hiders,
+    /// and exclusions for consistency
+    ValidateConfig {
+        /// optional address and viewing key of an admin
+        viewer: Option<ViewerInfo>,
+        /// optional permit used to verify admin identity.  If both viewer and permit
+        /// are provided, the viewer will be ignored
+        permit: Option<Permit>,
+    },
     /// creates new and unique genetic images.  This can only be called by an authorized minter
     NewGenes {
         /// address and viewing key of a minting contract
@@ -287,6 +414,10 @@ pub enum QueryMsg {
         entropy: String,
         /// the names of the background layer variants to use
         backgrounds: Vec<String>,
+        /// optional address for each background used to randomize that gene instead of the
+        /// sender, so genes for several recipients can be rolled together
+        #[serde(default)]
+        senders: Option<Vec<HumanAddr>>,
     },
     /// generates metadata from the input image vector
     TokenMetadata {
@@ -303,6 +434,23 @@ pub enum QueryMsg {
         /// address and viewing key of a reveal contract
         viewer: ViewerInfo,
     },
+    /// displays the number of minted genes with each trait variant
+    TraitCounts {},
+    /// displays the distribution of traits produced by rolling a number of genes with the
+    /// provided seed.  Nothing is recorded
+    SimulateGenes {
+        /// optional address and viewing key of an admin
+        viewer: Option<ViewerInfo>,
+        /// optional permit used to verify admin identity.  If both viewer and permit
+        /// are provided, the viewer will be ignored
+        permit: Option<Permit>,
+        /// seed for randomization
+        seed: String,
+        /// name of the background variant to use
+        background: String,
+        /// number of genes to roll
+        count: u16,
+    },
 }
 
 /// responses to queries
@@ -327,6 +475,8 @@ pub enum QueryAnswer {
         forced_cyclops: Option<String>,
         /// forced variant if jawless
         forced_jawless: Option<String>,
+        /// conditional weight tables and forced variants
+        conditionals: Vec<Conditional>,
         /// number of variants in this category
         variant_count: u8,
         /// paginated variants for this category
@@ -339,6 +489,12 @@ pub enum QueryAnswer {
         /// all the variant info
         info: VariantInfoPlus,
     },
+    /// displays the svg canvas that wraps every image
+    Canvas { canvas: SvgCanvas },
+    /// displays named svg fragments
+    Fragments { fragments: Vec<Fragment> },
+    /// displays the color palettes
+    Palettes { palettes: Vec<Palette> },
     /// response for both CommonMetadata and TokenMetadata
     Metadata {
         public_metadata: Option<Metadata>,
@@ -355,6 +511,10 @@ pub enum QueryAnswer {
         jaw_weight: u16,
         /// weight for jawless skulls
         jawless_weight: u16,
+        /// the categories and variants that determine a skull's archetype
+        archetype: Option<Archetype>,
+        /// the categories in the order they are drawn
+        render_order: Vec<String>,
     },
     /// displays the trait variants with dependencies (multiple layers)
     Dependencies {
@@ -368,6 +528,19 @@ pub enum QueryAnswer {
         count: u16,
         hiders: Vec<Dependencies>,
     },
+    /// displays the trait variants that can not appear with other trait variants
+    Exclusions {
+        /// number of exclusions
+        count: u16,
+        exclusions: Vec<Dependencies>,
+    },
+    /// displays any problems with the layer configuration
+    ValidateConfig {
+        /// true if no problems were found
+        valid: bool,
+        /// descriptions of every problem found
+        problems: Vec<String>,
+    },
     /// response from creating a new genetic images
     NewGenes { genes: Vec<GeneInfo> },
     /// info needed by alchemy/reveal contracts
@@ -379,6 +552,30 @@ pub enum QueryAnswer {
         /// category names
         category_names: Vec<String>,
     },
+    /// displays the number of minted genes with each trait variant
+    TraitCounts {
+        /// number of genes minted
+        total: u32,
+        /// number of minted genes with each variant in each category
+        histograms: Vec<CategoryHistogram>,
+        /// true if the genes minted before the trait counts were kept have been counted
+        complete: bool,
+    },
+    /// displays the distribution of traits produced by a simulation
+    SimulateGenes {
+        /// number of genes rolled
+        count: u16,
+        /// number of times each variant was rolled in each category
+        histograms: Vec<CategoryHistogram>,
+        /// number of jawless skulls
+        jawless: u32,
+        /// number of cyclops skulls
+        cyclops: u32,
+        /// number of failed uniqueness checks that caused traits to be rerolled
+        rerolls: u32,
+        /// number of times a gene had to be restarted from the archetype
+        restarts: u32,
+    },
 }
 
 /// genetic image information
@@ -392,6 +589,24 @@ pub struct GeneInfo {
     pub unique_check: Vec<u8>,
 }
 
+/// number of times each variant of a category was rolled
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct CategoryHistogram {
+    /// category name
+    pub category: String,
+    /// number of times each variant was rolled
+    pub variants: Vec<VariantCount>,
+}
+
+/// number of times a variant was rolled
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct VariantCount {
+    /// variant name
+    pub variant: String,
+    /// number of times rolled
+    pub count: u32,
+}
+
 /// trait variant information
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
 pub struct VariantInfo {
@@ -407,6 +622,12 @@ pub struct VariantInfo {
     pub jawless_weight: Option<u16>,
     /// randomization weight for cyclops
     pub cyclops_weight: Option<u16>,
+    /// optional name of the category this variant should be drawn behind
+    pub render_before: Option<String>,
+    /// optional base variant and color swaps used to draw this variant instead of an svg
+    pub recolor: Option<Recolor>,
+    /// optional svg animation block drawn over this variant when animations are enabled
+    pub animation: Option<String>,
 }
 
 /// trait variant information with its index and dependencies
@@ -431,6 +652,8 @@ pub struct CategoryInfo {
     pub forced_cyclops: Option<String>,
     /// forced variant if jawless
     pub forced_jawless: Option<String>,
+    /// optional conditional weight tables and forced variants
+    pub conditionals: Option<Vec<Conditional>>,
     /// variants for this category
     pub variants: Vec<VariantInfo>,
 }
@@ -473,6 +696,51 @@ pub struct ForcedVariants {
     pub forced_jawless: Option<String>,
 }
 
+/// a weight table or forced variant used when a previously rolled layer is a specific
+/// variant.  Variants added later get a weight of 0 in conditional weight tables
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct Conditional {
+    /// the layer that must have been rolled for this conditional to apply
+    pub condition: LayerId,
+    /// weight table to use if the condition is met
+    pub weights: Option<Vec<u16>>,
+    /// variant that must be used if the condition is met
+    pub forced: Option<String>,
+}
+
+impl Conditional {
+    /// Returns StdResult<StoredConditional> from creating a StoredConditional from a Conditional
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - a reference to the contract storage
+    /// * `category` - index of the category this conditional belongs to
+    pub fn to_stored<S: ReadonlyStorage>(
+        &self,
+        storage: &S,
+        category: u8,
+    ) -> StdResult<StoredConditional> {
+        let var_map = ReadonlyPrefixedStorage::multilevel(
+            &[PREFIX_VARIANT_MAP, &category.to_le_bytes()],
+            storage,
+        );
+        let forced = self
+            .forced
+            .as_ref()
+            .map(|f| {
+                may_load::<u8, _>(&var_map, f.as_bytes())?.ok_or_else(|| {
+                    StdError::generic_err(format!("Forced variant {} does not exist", f))
+                })
+            })
+            .transpose()?;
+        Ok(StoredConditional {
+            condition: self.condition.to_stored(storage)?,
+            weights: self.weights.clone(),
+            forced,
+        })
+    }
+}
+
 /// the address and viewing key making an authenticated query request
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
 pub struct ViewerInfo {
@@ -547,6 +815,255 @@ impl LayerId {
     }
 }
 
+/// the categories rolled first that determine a skull's archetype
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct Archetype {
+    /// name of the skull category
+    pub skull: String,
+    /// name of the jaw category.  A jawed skull uses the jaw variant with the same name
+    /// as its skull variant
+    pub jaw: String,
+    /// name of the eye type category
+    pub eye_type: String,
+    /// optional name of the eye type variant that means cyclops.  If omitted, no skull
+    /// is a cyclops
+    pub cyclops: Option<String>,
+    /// optional name of the jaw variant used by jawless skulls.  It is required if jawless
+    /// skulls can be rolled
+    pub jawless: Option<String>,
+    /// optional name of the category that shows its None variant when it is unknown
+    pub placeholder: Option<String>,
+    /// names of the categories rolled right after the archetype, in the order they are
+    /// rolled.  All other categories are rolled after these in index order
+    #[serde(default)]
+    pub roll_first: Vec<String>,
+}
+
+impl Archetype {
+    /// Returns StdResult<StoredArchetype> from creating a StoredArchetype from an Archetype
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - a reference to the contract storage
+    pub fn to_stored<S: ReadonlyStorage>(&self, storage: &S) -> StdResult<StoredArchetype> {
+        let cat_map = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY_MAP, storage);
+        let cat_idx = |name: &str| -> StdResult<u8> {
+            may_load::<u8, _>(&cat_map, name.as_bytes())?.ok_or_else(|| {
+                StdError::generic_err(format!("Category name:  {} does not exist", name))
+            })
+        };
+        let skull = cat_idx(&self.skull)?;
+        let jaw = cat_idx(&self.jaw)?;
+        let eye_type = cat_idx(&self.eye_type)?;
+        let var_idx = |category: &str, variant: &Option<String>| -> StdResult<Option<u8>> {
+            variant
+                .as_ref()
+                .map(|v| {
+                    LayerId {
+                        category: category.to_string(),
+                        variant: v.clone(),
+                    }
+                    .to_stored(storage)
+                    .map(|l| l.variant)
+                })
+                .transpose()
+        };
+        let cyclops = var_idx(&self.eye_type, &self.cyclops)?;
+        let jawless = var_idx(&self.jaw, &self.jawless)?;
+        let placeholder = self
+            .placeholder
+            .as_ref()
+            .map(|p| {
+                may_load::<u8, _>(&cat_map, p.as_bytes())?.ok_or_else(|| {
+                    StdError::generic_err(format!("Category name:  {} does not exist", p))
+                })
+            })
+            .transpose()?;
+        let archetype_cats = [skull, jaw, eye_type];
+        let mut roll_first: Vec<u8> = Vec::new();
+        for name in self.roll_first.iter() {
+            let idx: u8 = may_load(&cat_map, name.as_bytes())?.ok_or_else(|| {
+                StdError::generic_err(format!("Category name:  {} does not exist", name))
+            })?;
+            if idx == 0 || archetype_cats.contains(&idx) || roll_first.contains(&idx) {
+                return Err(StdError::generic_err(format!(
+                    "Category {} can not be listed in the roll first categories",
+                    name
+                )));
+            }
+            roll_first.push(idx);
+        }
+        Ok(StoredArchetype {
+            skull,
+            jaw,
+            eye_type,
+            cyclops,
+            jawless,
+            placeholder,
+            roll_first,
+        })
+    }
+}
+
+/// the archetype categories and variants by index
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StoredArchetype {
+    /// skull category index
+    pub skull: u8,
+    /// jaw category index
+    pub jaw: u8,
+    /// eye type category index
+    pub eye_type: u8,
+    /// optional index of the eye type variant that means cyclops
+    pub cyclops: Option<u8>,
+    /// optional index of the jaw variant used by jawless skulls
+    pub jawless: Option<u8>,
+    /// optional index of the category that shows its None variant when it is unknown
+    pub placeholder: Option<u8>,
+    /// indices of the categories rolled right after the archetype
+    pub roll_first: Vec<u8>,
+}
+
+impl StoredArchetype {
+    /// Returns StdResult<Archetype> from creating an Archetype from a StoredArchetype
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - a reference to the contract storage
+    pub fn to_display<S: ReadonlyStorage>(&self, storage: &S) -> StdResult<Archetype> {
+        let var_name = |category: u8, variant: Option<u8>| -> StdResult<Option<String>> {
+            variant
+                .map(|v| {
+                    StoredLayerId {
+                        category,
+                        variant: v,
+                    }
+                    .to_display(storage)
+                    .map(|l| l.variant)
+                })
+                .transpose()
+        };
+        let cat_store = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY, storage);
+        let cat_name = |idx: u8| -> StdResult<String> {
+            may_load::<Category, _>(&cat_store, &idx.to_le_bytes())?
+                .ok_or_else(|| StdError::generic_err("Category storage is corrupt"))
+                .map(|c| c.name)
+        };
+        Ok(Archetype {
+            skull: cat_name(self.skull)?,
+            jaw: cat_name(self.jaw)?,
+            eye_type: cat_name(self.eye_type)?,
+            cyclops: var_name(self.eye_type, self.cyclops)?,
+            jawless: var_name(self.jaw, self.jawless)?,
+            placeholder: self.placeholder.map(cat_name).transpose()?,
+            roll_first: self
+                .roll_first
+                .iter()
+                .map(|c| cat_name(*c))
+                .collect::<StdResult<Vec<String>>>()?,
+        })
+    }
+
+    /// Returns StdResult<Option<StoredArchetype>> of the archetype used when rolling.  If one
+    /// has not been set, the original "Skull", "Jaw Type", "Eye Type", and "Hair" categories
+    /// are used if they exist, with the "None" jaw for jawless skulls and the eye type
+    /// displayed as "Cyclops" for cyclops if those variants exist
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - a reference to the contract storage
+    /// * `roll` - a reference to the RollConfig
+    pub fn effective<S: ReadonlyStorage>(
+        storage: &S,
+        roll: &RollConfig,
+    ) -> StdResult<Option<StoredArchetype>> {
+        if roll.archetype.is_some() {
+            return Ok(roll.archetype.clone());
+        }
+        let cat_map = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY_MAP, storage);
+        let cat_idx = |name: &str| may_load::<u8, _>(&cat_map, name.as_bytes());
+        let (skull, jaw, eye_type) = match (
+            cat_idx("Skull")?,
+            cat_idx("Jaw Type")?,
+            cat_idx("Eye Type")?,
+        ) {
+            (Some(s), Some(j), Some(e)) => (s, j, e),
+            _ => return Ok(None),
+        };
+        let jaw_map =
+            ReadonlyPrefixedStorage::multilevel(&[PREFIX_VARIANT_MAP, &jaw.to_le_bytes()], storage);
+        let jawless: Option<u8> = may_load(&jaw_map, "None".as_bytes())?;
+        let cat_store = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY, storage);
+        let eye_cnt = may_load::<Category, _>(&cat_store, &eye_type.to_le_bytes())?
+            .map_or(0, |c| c.normal_weights.len());
+        let eye_store = ReadonlyPrefixedStorage::multilevel(
+            &[PREFIX_VARIANT, &eye_type.to_le_bytes()],
+            storage,
+        );
+        let mut cyclops: Option<u8> = None;
+        for var_idx in 0..eye_cnt {
+            let var_key = (var_idx as u8).to_le_bytes();
+            if may_load::<Variant, _>(&eye_store, &var_key)?
+                .filter(|v| v.display == "Cyclops")
+                .is_some()
+            {
+                cyclops = Some(var_idx as u8);
+                break;
+            }
+        }
+        let placeholder = cat_idx("Hair")?;
+        Ok(Some(StoredArchetype {
+            skull,
+            jaw,
+            eye_type,
+            cyclops,
+            jawless,
+            placeholder,
+            roll_first: Vec::new(),
+        }))
+    }
+
+    /// Returns Vec<u8> of every category index in the order they are rolled: the background,
+    /// the archetype categories, the roll first categories, then the rest by index
+    ///
+    /// # Arguments
+    ///
+    /// * `cat_cnt` - number of categories
+    pub fn roll_order(&self, cat_cnt: u8) -> Vec<u8> {
+        let mut order = vec![0, self.skull, self.jaw, self.eye_type];
+        order.extend(self.roll_first.iter());
+        let rest: Vec<u8> = (1..cat_cnt).filter(|c| !order.contains(c)).collect();
+        order.extend(rest);
+        order
+    }
+}
+
+/// Returns Vec<u8> of every category index in the order they are rolled, which is by index
+/// if there is no archetype
+///
+/// # Arguments
+///
+/// * `archetype` - optional reference to the archetype
+/// * `cat_cnt` - number of categories
+pub fn roll_order(archetype: Option<&StoredArchetype>, cat_cnt: u8) -> Vec<u8> {
+    archetype.map_or_else(|| (0..cat_cnt).collect(), |a| a.roll_order(cat_cnt))
+}
+
+/// Returns bool true if the first category is rolled before the second one
+///
+/// # Arguments
+///
+/// * `order` - every category index in the order they are rolled
+/// * `first` - index of the category that should be rolled first
+/// * `second` - index of the category that should be rolled second
+pub fn rolled_before(order: &[u8], first: u8, second: u8) -> bool {
+    let pos = |c: u8| order.iter().position(|o| *o == c);
+    match (pos(first), pos(second)) {
+        (Some(f), Some(s)) => f < s,
+        _ => false,
+    }
+}
+
 /// identifies a layer
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct StoredLayerId {
@@ -576,6 +1093,123 @@ impl StoredLayerId {
     }
 }
 
+/// a weight table or forced variant used when a previously rolled layer is a specific variant
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StoredConditional {
+    /// the layer that must have been rolled for this conditional to apply
+    pub condition: StoredLayerId,
+    /// weight table to use if the condition is met
+    pub weights: Option<Vec<u16>>,
+    /// index of the variant that must be used if the condition is met
+    pub forced: Option<u8>,
+}
+
+impl StoredConditional {
+    /// Returns StdResult<Conditional> from creating a Conditional from a StoredConditional
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - a reference to the contract storage
+    /// * `category` - index of the category this conditional belongs to
+    pub fn to_display<S: ReadonlyStorage>(
+        &self,
+        storage: &S,
+        category: u8,
+    ) -> StdResult<Conditional> {
+        let forced = self
+            .forced
+            .map(|variant| {
+                StoredLayerId { category, variant }
+                    .to_display(storage)
+                    .map(|l| l.variant)
+            })
+            .transpose()?;
+        Ok(Conditional {
+            condition: self.condition.to_display(storage)?,
+            weights: self.weights.clone(),
+            forced,
+        })
+    }
+}
+
+/// a variant drawn as another variant of the same category with some colors swapped
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Recolor {
+    /// name of the base variant in the same category
+    pub base: String,
+    /// colors to swap in the base variant's svg
+    pub swaps: Vec<ColorSwap>,
+}
+
+/// a hex color in a base svg and the hex color that replaces it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ColorSwap {
+    /// color in the base svg, written as `#rrggbb` or `#rgb`
+    pub from: String,
+    /// color that replaces it, written as `#rrggbb` or `#rgb`
+    pub to: String,
+}
+
+/// a variant drawn as another variant of the same category with some colors swapped
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StoredRecolor {
+    /// index of the base variant in the same category
+    pub base: u8,
+    /// rgb values of the colors to swap
+    pub swaps: Vec<StoredColorSwap>,
+}
+
+impl StoredRecolor {
+    /// Returns StdResult<Recolor> from creating a Recolor from a StoredRecolor
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - a reference to the contract storage
+    /// * `category` - index of the category this recolor belongs to
+    pub fn to_display<S: ReadonlyStorage>(&self, storage: &S, category: u8) -> StdResult<Recolor> {
+        let base = StoredLayerId {
+            category,
+            variant: self.base,
+        }
+        .to_display(storage)?
+        .variant;
+        Ok(Recolor {
+            base,
+            swaps: self
+                .swaps
+                .iter()
+                .map(|s| ColorSwap {
+                    from: hex_color(&s.from),
+                    to: hex_color(&s.to),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// rgb values of a color in a base svg and the color that replaces it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StoredColorSwap {
+    /// rgb of the color in the base svg
+    pub from: [u8; 3],
+    /// rgb of the color that replaces it
+    pub to: [u8; 3],
+}
+
+impl ColorSwap {
+    /// Returns StdResult<StoredColorSwap> from parsing the hex colors of a ColorSwap
+    pub fn to_stored(&self) -> StdResult<StoredColorSwap> {
+        let parse = |c: &str| {
+            parse_hex_color(c)
+                .ok_or_else(|| StdError::generic_err(format!("Invalid hex color: {}", c)))
+        };
+        Ok(StoredColorSwap {
+            from: parse(&self.from)?,
+            to: parse(&self.to)?,
+        })
+    }
+}
+
 /// the metadata common to all NFTs
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CommonMetadata {
@@ -585,6 +1219,183 @@ pub struct CommonMetadata {
     pub private: Option<Metadata>,
 }
 
+/// the svg element that wraps every image
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SvgCanvas {
+    /// the viewBox of the svg
+    pub view_box: String,
+    /// optional width of the svg
+    pub width: Option<String>,
+    /// optional height of the svg
+    pub height: Option<String>,
+    /// additional attributes of the svg element
+    pub attributes: Vec<SvgAttribute>,
+    /// optional contents of a defs block shared by all variants
+    pub defs: Option<String>,
+    /// optional contents of a style block shared by all variants
+    pub style: Option<String>,
+    /// true if variant animations are drawn and the animated svg is also provided as the
+    /// `animation_url`.  Defaults to false
+    #[serde(default)]
+    pub animations_enabled: bool,
+}
+
+impl Default for SvgCanvas {
+    fn default() -> Self {
+        SvgCanvas {
+            view_box: "0 -0.5 24 24".to_string(),
+            width: None,
+            height: None,
+            attributes: vec![SvgAttribute {
+                name: "shape-rendering".to_string(),
+                value: "crispEdges".to_string(),
+            }],
+            defs: None,
+            style: None,
+            animations_enabled: false,
+        }
+    }
+}
+
+impl SvgCanvas {
+    /// Returns StdResult<()> after verifying the attribute names are valid
+    pub fn validate(&self) -> StdResult<()> {
+        let reserved = ["xmlns", "viewBox", "width", "height"];
+        for attr in self.attributes.iter() {
+            if attr.name.is_empty()
+                || reserved.contains(&attr.name.as_str())
+                || !attr
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == ':')
+            {
+                return Err(StdError::generic_err(format!(
+                    "Invalid svg attribute name: {}",
+                    attr.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns String of the opening svg tag followed by any shared defs and style
+    pub fn open(&self) -> String {
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{}""#,
+            escape_attr(&self.view_box)
+        );
+        if let Some(width) = self.width.as_ref() {
+            svg.push_str(&format!(r#" width="{}""#, escape_attr(width)));
+        }
+        if let Some(height) = self.height.as_ref() {
+            svg.push_str(&format!(r#" height="{}""#, escape_attr(height)));
+        }
+        for attr in self.attributes.iter() {
+            svg.push_str(&format!(r#" {}="{}""#, attr.name, escape_attr(&attr.value)));
+        }
+        svg.push('>');
+        if let Some(defs) = self.defs.as_ref() {
+            svg.push_str(&format!("<defs>{}</defs>", defs));
+        }
+        if let Some(style) = self.style.as_ref() {
+            svg.push_str(&format!("<style>{}</style>", style));
+        }
+        svg
+    }
+}
+
+/// a named svg fragment shared by variants
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Fragment {
+    /// fragment name
+    pub name: String,
+    /// svg of the fragment, which may use `{pal:index}` in place of palette colors
+    pub svg: String,
+}
+
+impl Fragment {
+    /// Returns StdResult<()> after verifying the name is valid and the fragment does not
+    /// reference other fragments
+    pub fn validate(&self) -> StdResult<()> {
+        validate_ref_name(&self.name, "fragment")?;
+        if self.svg.contains(FRAGMENT_REF) {
+            return Err(StdError::generic_err(format!(
+                "Fragment {} can not reference other fragments",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// a named list of colors substituted into fragments
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Palette {
+    /// palette name
+    pub name: String,
+    /// colors referenced by their index with `{pal:index}`
+    pub colors: Vec<String>,
+}
+
+impl Palette {
+    /// Returns StdResult<()> after verifying the name and colors are valid
+    pub fn validate(&self) -> StdResult<()> {
+        validate_ref_name(&self.name, "palette")?;
+        if let Some(color) = self
+            .colors
+            .iter()
+            .find(|c| c.is_empty() || c.contains(|ch| "\"'<>{}".contains(ch)))
+        {
+            return Err(StdError::generic_err(format!(
+                "Invalid color {} in palette {}",
+                color, self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Returns StdResult<()> after verifying a fragment or palette name can be used in a reference
+///
+/// # Arguments
+///
+/// * `name` - the name to check
+/// * `kind` - what is being named
+fn validate_ref_name(name: &str, kind: &str) -> StdResult<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(StdError::generic_err(format!(
+            "Invalid {} name: {}",
+            kind, name
+        )));
+    }
+    Ok(())
+}
+
+/// an attribute of the svg element
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SvgAttribute {
+    /// attribute name
+    pub name: String,
+    /// attribute value
+    pub value: String,
+}
+
+/// Returns String of an attribute value with its special characters escaped
+///
+/// # Arguments
+///
+/// * `value` - the attribute value
+fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
+
 /// describes a trait that has multiple layers
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct StoredDependencies {
@@ -0,0 +1,350 @@
+use cosmwasm_std::{ReadonlyStorage, StdError, StdResult};
+use cosmwasm_storage::ReadonlyPrefixedStorage;
+
+use crate::msg::{Palette, StoredColorSwap};
+use crate::state::{Variant, PALETTES_KEY, PREFIX_FRAGMENT, PREFIX_VARIANT};
+use crate::storage::may_load;
+
+/// opening of a reference to a shared fragment, written as `{frag:name}` or
+/// `{frag:name:palette}`
+pub const FRAGMENT_REF: &str = "{frag:";
+/// opening of a reference to a palette color inside a fragment, written as `{pal:index}`
+pub const PALETTE_REF: &str = "{pal:";
+
+/// Returns StdResult<Option<String>> of the svg a variant draws, after expanding its fragment
+/// references and swapping the colors of its base variant if it is a recolor
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `category` - index of the category the variant belongs to
+/// * `var` - a reference to the variant
+pub fn variant_svg<S: ReadonlyStorage>(
+    storage: &S,
+    category: u8,
+    var: &Variant,
+) -> StdResult<Option<String>> {
+    if let Some(recolor) = var.recolor.as_ref() {
+        let var_store = ReadonlyPrefixedStorage::multilevel(
+            &[PREFIX_VARIANT, &category.to_le_bytes()],
+            storage,
+        );
+        let base: Variant =
+            may_load(&var_store, &recolor.base.to_le_bytes())?.ok_or_else(|| {
+                StdError::generic_err(format!(
+                    "Base variant {} of recolor {} does not exist",
+                    recolor.base, var.name
+                ))
+            })?;
+        // a recolor can only be based on a variant with its own svg
+        if base.recolor.is_some() {
+            return Err(StdError::generic_err(format!(
+                "Base variant {} of recolor {} is also a recolor",
+                base.name, var.name
+            )));
+        }
+        let svg = base.svg.ok_or_else(|| {
+            StdError::generic_err(format!(
+                "Base variant {} of recolor {} does not have an svg",
+                base.name, var.name
+            ))
+        })?;
+        return Ok(Some(swap_colors(
+            &expand_fragments(storage, &svg)?,
+            &recolor.swaps,
+        )));
+    }
+    var.svg
+        .as_ref()
+        .map(|svg| expand_fragments(storage, svg))
+        .transpose()
+}
+
+/// Returns StdResult<String> of an svg with all its fragment references expanded
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `svg` - the svg that may contain fragment references
+pub fn expand_fragments<S: ReadonlyStorage>(storage: &S, svg: &str) -> StdResult<String> {
+    // nothing to do if there are no references
+    if !svg.contains(FRAGMENT_REF) {
+        return Ok(svg.to_string());
+    }
+    let frag_store = ReadonlyPrefixedStorage::new(PREFIX_FRAGMENT, storage);
+    let mut palettes: Option<Vec<Palette>> = None;
+    let mut expanded = String::new();
+    let mut rest = svg;
+    while let Some(start) = rest.find(FRAGMENT_REF) {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + FRAGMENT_REF.len()..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| StdError::generic_err("Unterminated fragment reference"))?;
+        let mut parts = after[..end].splitn(2, ':');
+        let name = parts.next().unwrap_or_default();
+        let fragment: String = may_load(&frag_store, name.as_bytes())?
+            .ok_or_else(|| StdError::generic_err(format!("Fragment {} does not exist", name)))?;
+        let colors: &[String] = if let Some(pal_name) = parts.next() {
+            // only load the palettes if they are used
+            if palettes.is_none() {
+                palettes = Some(may_load(storage, PALETTES_KEY)?.unwrap_or_else(Vec::new));
+            }
+            &palettes
+                .as_ref()
+                .and_then(|p| p.iter().find(|p| p.name == pal_name))
+                .ok_or_else(|| {
+                    StdError::generic_err(format!("Palette {} does not exist", pal_name))
+                })?
+                .colors
+        } else {
+            &[]
+        };
+        expanded.push_str(&apply_palette(&fragment, colors)?);
+        rest = &after[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Returns (Vec<String>, Vec<String>) of the names of the fragments and palettes an svg
+/// refers to
+///
+/// # Arguments
+///
+/// * `svg` - the svg that may contain fragment references
+pub fn references(svg: &str) -> (Vec<String>, Vec<String>) {
+    let mut fragments: Vec<String> = Vec::new();
+    let mut palettes: Vec<String> = Vec::new();
+    let mut rest = svg;
+    while let Some(start) = rest.find(FRAGMENT_REF) {
+        let after = &rest[start + FRAGMENT_REF.len()..];
+        let end = if let Some(e) = after.find('}') {
+            e
+        } else {
+            break;
+        };
+        let mut parts = after[..end].splitn(2, ':');
+        if let Some(name) = parts.next().filter(|n| !fragments.iter().any(|f| f == n)) {
+            fragments.push(name.to_string());
+        }
+        if let Some(name) = parts.next().filter(|n| !palettes.iter().any(|p| p == n)) {
+            palettes.push(name.to_string());
+        }
+        rest = &after[end + 1..];
+    }
+    (fragments, palettes)
+}
+
+/// Returns StdResult<String> of a fragment with its palette color references replaced
+///
+/// # Arguments
+///
+/// * `fragment` - the fragment svg
+/// * `colors` - the colors of the palette used with this fragment
+fn apply_palette(fragment: &str, colors: &[String]) -> StdResult<String> {
+    let mut applied = String::new();
+    let mut rest = fragment;
+    while let Some(start) = rest.find(PALETTE_REF) {
+        applied.push_str(&rest[..start]);
+        let after = &rest[start + PALETTE_REF.len()..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| StdError::generic_err("Unterminated palette color reference"))?;
+        let color = after[..end]
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| colors.get(i))
+            .ok_or_else(|| {
+                StdError::generic_err(format!("Palette color {} is not defined", &after[..end]))
+            })?;
+        applied.push_str(color);
+        rest = &after[end + 1..];
+    }
+    applied.push_str(rest);
+    Ok(applied)
+}
+
+/// Returns String of an svg with every hex color in the swap list replaced
+///
+/// # Arguments
+///
+/// * `svg` - the base svg
+/// * `swaps` - the colors to swap
+pub fn swap_colors(svg: &str, swaps: &[StoredColorSwap]) -> String {
+    let mut swapped = String::new();
+    let mut rest = svg;
+    while let Some(start) = rest.find('#') {
+        swapped.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let len = after
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or_else(|| after.len());
+        let color = &rest[start..start + 1 + len];
+        // only whole colors are swapped, so other uses of '#' are left alone
+        let replacement = parse_hex_color(color)
+            .and_then(|rgb| swaps.iter().find(|s| s.from == rgb))
+            .map(|s| hex_color(&s.to));
+        swapped.push_str(replacement.as_deref().unwrap_or(color));
+        rest = &after[len..];
+    }
+    swapped.push_str(rest);
+    swapped
+}
+
+/// Returns Option<[u8; 3]> of the rgb values of a color written as `#rrggbb` or `#rgb`
+///
+/// # Arguments
+///
+/// * `color` - the hex color
+pub fn parse_hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+    match hex.len() {
+        6 => {
+            let mut rgb = [0u8; 3];
+            for (i, c) in rgb.iter_mut().enumerate() {
+                *c = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+            }
+            Some(rgb)
+        }
+        // each digit of the short form is doubled
+        3 => Some([digit(0)? * 17, digit(1)? * 17, digit(2)? * 17]),
+        _ => None,
+    }
+}
+
+/// Returns String of rgb values written as a `#rrggbb` hex color
+///
+/// # Arguments
+///
+/// * `rgb` - the rgb values
+pub fn hex_color(rgb: &[u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::StoredRecolor;
+    use crate::storage::save;
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_storage::PrefixedStorage;
+
+    fn swap(from: [u8; 3], to: [u8; 3]) -> StoredColorSwap {
+        StoredColorSwap { from, to }
+    }
+
+    fn variant(name: &str, svg: Option<&str>, recolor: Option<StoredRecolor>) -> Variant {
+        Variant {
+            name: name.to_string(),
+            svg: svg.map(|s| s.to_string()),
+            display: name.to_string(),
+            render_before: None,
+            recolor,
+            animation: None,
+        }
+    }
+
+    fn store_variants(storage: &mut MockStorage, category: u8, variants: &[Variant]) {
+        let mut var_store =
+            PrefixedStorage::multilevel(&[PREFIX_VARIANT, &category.to_le_bytes()], storage);
+        for (idx, var) in variants.iter().enumerate() {
+            save(&mut var_store, &(idx as u8).to_le_bytes(), var).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#1a2B3c"), Some([0x1a, 0x2b, 0x3c]));
+        assert_eq!(parse_hex_color("#f0a"), Some([0xff, 0x00, 0xaa]));
+        assert_eq!(parse_hex_color("1a2b3c"), None);
+        assert_eq!(parse_hex_color("#1a2b3"), None);
+        assert_eq!(parse_hex_color("#1g2b3c"), None);
+        assert_eq!(hex_color(&[0x1a, 0x2b, 0x3c]), "#1a2b3c");
+    }
+
+    #[test]
+    fn test_swap_colors() {
+        let svg = r##"<path stroke="#FF0000" d="M1 1h1"/><path stroke="#f00" d="M2 2h1"/><path stroke="#00ff00" fill="url(#grad)" d="M3 3h1"/><path stroke="#0000ff" d="M4 4h1"/>"##;
+        let swaps = vec![
+            swap([0xff, 0, 0], [0, 0, 0xff]),
+            swap([0, 0, 0xff], [0x12, 0x34, 0x56]),
+        ];
+        // swaps are not chained, and colors not in the list and other uses of '#' are untouched
+        assert_eq!(
+            swap_colors(svg, &swaps),
+            r##"<path stroke="#0000ff" d="M1 1h1"/><path stroke="#0000ff" d="M2 2h1"/><path stroke="#00ff00" fill="url(#grad)" d="M3 3h1"/><path stroke="#123456" d="M4 4h1"/>"##
+        );
+        assert_eq!(swap_colors("no colors", &swaps), "no colors");
+        assert_eq!(swap_colors("ends with #", &swaps), "ends with #");
+    }
+
+    #[test]
+    fn test_references() {
+        let svg = "<g>{frag:eye}{frag:eye:blue}{frag:lid:blue}{frag:lid:red}</g>{frag:open";
+        assert_eq!(
+            references(svg),
+            (
+                vec!["eye".to_string(), "lid".to_string()],
+                vec!["blue".to_string(), "red".to_string()]
+            )
+        );
+        assert_eq!(references("<g/>"), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn test_variant_svg_recolor() {
+        let mut storage = MockStorage::new();
+        let recolor = StoredRecolor {
+            base: 1,
+            swaps: vec![swap([0xff, 0, 0], [0, 0xff, 0])],
+        };
+        let variants = vec![
+            variant("None", None, None),
+            variant(
+                "Red",
+                Some(r##"<path stroke="#ff0000" d="M1 1h1"/>"##),
+                None,
+            ),
+            variant("Green", None, Some(recolor.clone())),
+            variant(
+                "Broken",
+                None,
+                Some(StoredRecolor {
+                    base: 0,
+                    ..recolor.clone()
+                }),
+            ),
+            variant(
+                "Chained",
+                None,
+                Some(StoredRecolor {
+                    base: 2,
+                    ..recolor.clone()
+                }),
+            ),
+            variant("Missing", None, Some(StoredRecolor { base: 9, ..recolor })),
+        ];
+        store_variants(&mut storage, 3, &variants);
+
+        assert_eq!(variant_svg(&storage, 3, &variants[0]).unwrap(), None);
+        assert_eq!(
+            variant_svg(&storage, 3, &variants[1]).unwrap(),
+            Some(r##"<path stroke="#ff0000" d="M1 1h1"/>"##.to_string())
+        );
+        assert_eq!(
+            variant_svg(&storage, 3, &variants[2]).unwrap(),
+            Some(r##"<path stroke="#00ff00" d="M1 1h1"/>"##.to_string())
+        );
+        // the base variant must have its own svg
+        assert!(variant_svg(&storage, 3, &variants[3]).is_err());
+        assert!(variant_svg(&storage, 3, &variants[4]).is_err());
+        assert!(variant_svg(&storage, 3, &variants[5]).is_err());
+        // the base variant is looked up in the recolor's own category
+        assert!(variant_svg(&storage, 4, &variants[2]).is_err());
+    }
+}
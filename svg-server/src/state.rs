@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::msg::{StoredArchetype, StoredConditional, StoredRecolor};
+
 /// storage key for the admins list
 pub const ADMINS_KEY: &[u8] = b"admin";
 /// storage key for the viewers list
@@ -16,8 +18,16 @@ pub const ROLL_CONF_KEY: &[u8] = b"roolcf";
 pub const DEPENDENCIES_KEY: &[u8] = b"depend";
 /// storage key for the variant that hide others
 pub const HIDERS_KEY: &[u8] = b"hider";
+/// storage key for the variants that can not appear together
+pub const EXCLUSIONS_KEY: &[u8] = b"exclude";
+/// storage key for the trait counts of minted genes
+pub const MINTED_TRAITS_KEY: &[u8] = b"mnttrts";
 /// storage key for the common metadata
 pub const METADATA_KEY: &[u8] = b"metadata";
+/// storage key for the svg canvas
+pub const CANVAS_KEY: &[u8] = b"canvas";
+/// storage key for the color palettes
+pub const PALETTES_KEY: &[u8] = b"palettes";
 /// storage prefix for mapping a category name to its index
 pub const PREFIX_CATEGORY_MAP: &[u8] = b"catemap";
 /// storage prefix for mapping a variant name to its index
@@ -26,6 +36,12 @@ pub const PREFIX_VARIANT_MAP: &[u8] = b"vrntmap";
 pub const PREFIX_CATEGORY: &[u8] = b"category";
 /// prefix for the storage of category variants
 pub const PREFIX_VARIANT: &[u8] = b"variant";
+/// prefix for the storage of svg fragments
+pub const PREFIX_FRAGMENT: &[u8] = b"fragment";
+/// prefix for the storage of the variants that use each fragment
+pub const PREFIX_FRAGMENT_USERS: &[u8] = b"fragusers";
+/// prefix for the storage of the variants that use each palette
+pub const PREFIX_PALETTE_USERS: &[u8] = b"palusers";
 /// prefix for storage of viewing keys
 pub const PREFIX_VIEW_KEY: &[u8] = b"viewkey";
 /// prefix for storage of genes
@@ -33,6 +49,17 @@ pub const PREFIX_GENE: &[u8] = b"gene";
 /// prefix for the storage of revoked permits
 pub const PREFIX_REVOKED_PERMITS: &str = "revoke";
 
+/// trait counts of minted genes
+#[derive(Serialize, Deserialize)]
+pub struct MintedTraits {
+    /// number of genes minted
+    pub total: u32,
+    /// number of minted genes with each variant index in each category index
+    pub counts: Vec<Vec<u32>>,
+    /// true if the genes minted before the counts were kept have been counted
+    pub backfilled: bool,
+}
+
 /// trait category
 #[derive(Serialize, Deserialize)]
 pub struct Category {
@@ -48,6 +75,9 @@ pub struct Category {
     pub jawless_weights: Option<Vec<u16>>,
     /// randomization weight table for cyclops
     pub cyclops_weights: Option<Vec<u16>>,
+    /// weight tables and forced variants that apply when a previously rolled layer is a
+    /// specific variant.  The first one whose condition is met is used
+    pub conditionals: Vec<StoredConditional>,
 }
 
 /// category variant
@@ -59,6 +89,13 @@ pub struct Variant {
     pub svg: Option<String>,
     /// display name
     pub display: String,
+    /// optional index of the category this variant is drawn behind instead of its own
+    /// category's render position
+    pub render_before: Option<u8>,
+    /// optional base variant and color swaps used to draw this variant instead of an svg
+    pub recolor: Option<StoredRecolor>,
+    /// optional svg animation block drawn over this variant when animations are enabled
+    pub animation: Option<String>,
 }
 
 /// config values needed when rolling a new NFT
@@ -70,4 +107,8 @@ pub struct RollConfig {
     pub skip: Vec<u8>,
     /// weights for drawing jawed vs jawless
     pub jaw_weights: Vec<u16>,
+    /// the archetype categories and variants
+    pub archetype: Option<StoredArchetype>,
+    /// category indices in the order their layers are drawn
+    pub render_order: Vec<u8>,
 }
@@ -0,0 +1,578 @@
+use cosmwasm_std::{ReadonlyStorage, StdError, StdResult};
+use cosmwasm_storage::ReadonlyPrefixedStorage;
+
+use crate::msg::{roll_order, rolled_before, StoredArchetype, StoredDependencies, StoredLayerId};
+use crate::render::{expand_fragments, variant_svg};
+use crate::state::{
+    Category, RollConfig, Variant, DEPENDENCIES_KEY, EXCLUSIONS_KEY, HIDERS_KEY, PREFIX_CATEGORY,
+    PREFIX_CATEGORY_MAP, PREFIX_VARIANT, PREFIX_VARIANT_MAP, ROLL_CONF_KEY,
+};
+use crate::storage::{load, may_load};
+
+/// the parts of the layer configuration a change can affect
+pub enum Scope {
+    /// everything
+    All,
+    /// the roll config, the archetype, and the category weight tables and conditionals that
+    /// depend on them
+    Roll,
+    /// the listed categories, their variants, and everything that refers to them
+    Categories(Vec<u8>),
+    /// the dependencies, hiders, and exclusions
+    Lists,
+    /// the svgs and animations of the listed variants
+    Variants(Vec<StoredLayerId>),
+}
+
+impl Scope {
+    /// Returns bool true if the roll config should be checked
+    fn roll(&self) -> bool {
+        match self {
+            Scope::All | Scope::Roll => true,
+            _ => false,
+        }
+    }
+
+    /// Returns bool true if a category should be checked
+    ///
+    /// # Arguments
+    ///
+    /// * `idx` - index of the category
+    fn category(&self, idx: u8) -> bool {
+        match self {
+            Scope::All | Scope::Roll => true,
+            Scope::Categories(cats) => cats.contains(&idx),
+            _ => false,
+        }
+    }
+
+    /// Returns bool true if the variants of a category should be checked
+    ///
+    /// # Arguments
+    ///
+    /// * `idx` - index of the category
+    fn variants(&self, idx: u8) -> bool {
+        match self {
+            Scope::All => true,
+            Scope::Categories(cats) => cats.contains(&idx),
+            _ => false,
+        }
+    }
+
+    /// Returns bool true if a list entry should be checked
+    ///
+    /// # Arguments
+    ///
+    /// * `dep` - a reference to the list entry
+    fn entry(&self, dep: &StoredDependencies) -> bool {
+        match self {
+            Scope::All | Scope::Lists => true,
+            Scope::Categories(cats) => {
+                cats.contains(&dep.id.category)
+                    || dep.correlated.iter().any(|l| cats.contains(&l.category))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Returns StdResult<()>
+///
+/// errors with a list of every problem found if the part of the layer configuration a change
+/// affected is inconsistent
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `scope` - a reference to the parts of the layer configuration to check
+pub fn check_config<S: ReadonlyStorage>(storage: &S, scope: &Scope) -> StdResult<()> {
+    let problems = validate_scope(storage, scope)?;
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(StdError::generic_err(format!(
+            "Invalid layer configuration: {}",
+            problems.join("; ")
+        )))
+    }
+}
+
+/// Returns StdResult<Vec<String>>
+///
+/// checks the categories, variants, weight tables, roll config, dependencies, hiders, and
+/// exclusions for consistency, and returns a description of every problem found
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+pub fn validate_config<S: ReadonlyStorage>(storage: &S) -> StdResult<Vec<String>> {
+    validate_scope(storage, &Scope::All)
+}
+
+/// Returns StdResult<Vec<String>>
+///
+/// checks part of the layer configuration for consistency, and returns a description of
+/// every problem found
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `scope` - a reference to the parts of the layer configuration to check
+fn validate_scope<S: ReadonlyStorage>(storage: &S, scope: &Scope) -> StdResult<Vec<String>> {
+    let mut problems: Vec<String> = Vec::new();
+    // only the rendering of the listed variants can be affected
+    if let Scope::Variants(layers) = scope {
+        let cat_store = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY, storage);
+        for layer in layers.iter() {
+            let var_store = ReadonlyPrefixedStorage::multilevel(
+                &[PREFIX_VARIANT, &layer.category.to_le_bytes()],
+                storage,
+            );
+            let cat_name = may_load::<Category, _>(&cat_store, &layer.category.to_le_bytes())?
+                .map_or_else(|| layer.category.to_string(), |c| c.name);
+            if let Some(var) = may_load::<Variant, _>(&var_store, &layer.variant.to_le_bytes())? {
+                check_render(storage, layer.category, &cat_name, &var, &mut problems);
+            } else {
+                problems.push(format!(
+                    "Variant {} of category {} is missing",
+                    layer.variant, cat_name
+                ));
+            }
+        }
+        return Ok(problems);
+    }
+    let roll: RollConfig = load(storage, ROLL_CONF_KEY)?;
+    let cat_store = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY, storage);
+    let cat_map = ReadonlyPrefixedStorage::new(PREFIX_CATEGORY_MAP, storage);
+    // keep a None in place of any missing category so the indices still line up
+    let mut cats: Vec<Option<Category>> = Vec::new();
+    for idx in 0..roll.cat_cnt {
+        let may_cat: Option<Category> = may_load(&cat_store, &idx.to_le_bytes())?;
+        if let Some(cat) = may_cat.as_ref().filter(|_| scope.category(idx)) {
+            if may_load::<u8, _>(&cat_map, cat.name.as_bytes())? != Some(idx) {
+                problems.push(format!(
+                    "Category name {} does not map to index {}",
+                    cat.name, idx
+                ));
+            }
+        } else if may_cat.is_none() {
+            problems.push(format!("Category {} is missing", idx));
+        }
+        cats.push(may_cat);
+    }
+    let var_cnt = |category: u8| {
+        cats.get(category as usize)
+            .and_then(|c| c.as_ref())
+            .map(|c| c.normal_weights.len())
+    };
+    let layer_exists =
+        |l: &StoredLayerId| var_cnt(l.category).map_or(false, |n| (l.variant as usize) < n);
+    let describe = |l: &StoredLayerId| -> StdResult<String> {
+        if layer_exists(l) {
+            l.to_display(storage)
+                .map(|d| format!("{}: {}", d.category, d.variant))
+        } else {
+            Ok(format!("category {} variant {}", l.category, l.variant))
+        }
+    };
+    // the archetype categories and roll first categories are rolled before all others
+    let archetype = StoredArchetype::effective(storage, &roll)?;
+    let order = roll_order(archetype.as_ref(), roll.cat_cnt);
+
+    // check the jaw weights
+    if scope.roll() {
+        if roll.jaw_weights.len() != 2 {
+            problems.push("Jaw weight table must have 2 weights".to_string());
+        } else if let Some(prob) = weight_problem(&roll.jaw_weights, 2, true) {
+            problems.push(format!("Jaw weight table {}", prob));
+        }
+    }
+    // check the render order
+    let mut sorted = roll.render_order.clone();
+    sorted.sort_unstable();
+    if sorted != (0..roll.cat_cnt).collect::<Vec<u8>>() {
+        problems.push("The render order must list every category exactly once".to_string());
+    }
+    // check the skipped categories
+    for skip in roll.skip.iter().filter(|s| scope.category(**s)) {
+        if var_cnt(*skip).is_none() {
+            problems.push(format!("Skipped category {} does not exist", skip));
+        } else {
+            let var_map = ReadonlyPrefixedStorage::multilevel(
+                &[PREFIX_VARIANT_MAP, &skip.to_le_bytes()],
+                storage,
+            );
+            if may_load::<u8, _>(&var_map, "None".as_bytes())?.is_none() {
+                problems.push(format!(
+                    "Skipped category {} does not have a None variant",
+                    describe_cat(&cats, *skip)
+                ));
+            }
+        }
+    }
+    // check the archetype
+    if let Some(arch) = archetype.as_ref().filter(|a| {
+        scope.roll()
+            || [a.skull, a.jaw, a.eye_type]
+                .iter()
+                .chain(a.roll_first.iter())
+                .any(|c| scope.category(*c))
+    }) {
+        let mut layers: Vec<StoredLayerId> = Vec::new();
+        if let Some(cyclops) = arch.cyclops {
+            layers.push(StoredLayerId {
+                category: arch.eye_type,
+                variant: cyclops,
+            });
+        }
+        if let Some(jawless) = arch.jawless {
+            layers.push(StoredLayerId {
+                category: arch.jaw,
+                variant: jawless,
+            });
+        } else if roll.jaw_weights.get(1).map_or(false, |w| *w > 0) {
+            problems.push(
+                "Jawless skulls can be rolled, but there is no jaw variant for them".to_string(),
+            );
+        }
+        for cat_idx in [arch.jaw, arch.eye_type].iter() {
+            if var_cnt(*cat_idx).is_none() {
+                problems.push(format!("Archetype category {} does not exist", cat_idx));
+            }
+        }
+        if let Some(n) = var_cnt(arch.skull) {
+            // a jawed skull uses the jaw variant with the same name as its skull variant
+            let skull_store = ReadonlyPrefixedStorage::multilevel(
+                &[PREFIX_VARIANT, &arch.skull.to_le_bytes()],
+                storage,
+            );
+            let jaw_map = ReadonlyPrefixedStorage::multilevel(
+                &[PREFIX_VARIANT_MAP, &arch.jaw.to_le_bytes()],
+                storage,
+            );
+            for var_idx in 0..n as u8 {
+                if let Some(skull) = may_load::<Variant, _>(&skull_store, &var_idx.to_le_bytes())? {
+                    if may_load::<u8, _>(&jaw_map, skull.name.as_bytes())?.is_none() {
+                        problems.push(format!(
+                            "Jaw category does not have a variant for skull variant {}",
+                            skull.name
+                        ));
+                    }
+                }
+            }
+        } else {
+            problems.push(format!("Skull category {} does not exist", arch.skull));
+        }
+        if let Some(placeholder) = arch.placeholder.filter(|p| var_cnt(*p).is_none()) {
+            problems.push(format!(
+                "Placeholder category {} does not exist",
+                placeholder
+            ));
+        }
+        for first in arch.roll_first.iter() {
+            if var_cnt(*first).is_none() {
+                problems.push(format!("Roll first category {} does not exist", first));
+            } else if *first == 0 || [arch.skull, arch.jaw, arch.eye_type].contains(first) {
+                problems.push(format!(
+                    "Category {} can not be listed in the roll first categories",
+                    describe_cat(&cats, *first)
+                ));
+            }
+        }
+        for layer in layers.iter() {
+            if !layer_exists(layer) {
+                problems.push(format!(
+                    "Archetype layer {} does not exist",
+                    describe(layer)?
+                ));
+            }
+        }
+    }
+    // check each category and its variants
+    for (idx, may_cat) in cats.iter().enumerate() {
+        let cat = if let Some(c) = may_cat {
+            c
+        } else {
+            continue;
+        };
+        let idx = idx as u8;
+        // also check categories whose conditions refer to a category being checked
+        if !scope.category(idx)
+            && !cat
+                .conditionals
+                .iter()
+                .any(|c| scope.category(c.condition.category))
+        {
+            continue;
+        }
+        let n = cat.normal_weights.len();
+        if n == 0 {
+            problems.push(format!("Category {} has no variants", cat.name));
+            continue;
+        }
+        // backgrounds, skipped categories, and jaws are never drawn from a weight table, and
+        // nothing is drawn until the archetype is set
+        let drawn = idx != 0
+            && !roll.skip.contains(&idx)
+            && archetype.as_ref().map_or(false, |a| a.jaw != idx);
+        if let Some(prob) = weight_problem(&cat.normal_weights, n, drawn) {
+            problems.push(format!(
+                "Normal weight table of category {} {}",
+                cat.name, prob
+            ));
+        }
+        if let Some(prob) = cat
+            .jawless_weights
+            .as_ref()
+            .and_then(|w| weight_problem(w, n, drawn))
+        {
+            problems.push(format!(
+                "Jawless weight table of category {} {}",
+                cat.name, prob
+            ));
+        }
+        if let Some(prob) = cat
+            .cyclops_weights
+            .as_ref()
+            .and_then(|w| weight_problem(w, n, drawn))
+        {
+            problems.push(format!(
+                "Cyclops weight table of category {} {}",
+                cat.name, prob
+            ));
+        }
+        for forced in cat.forced_cyclops.iter().chain(cat.forced_jawless.iter()) {
+            if *forced as usize >= n {
+                problems.push(format!(
+                    "Forced variant {} of category {} does not exist",
+                    forced, cat.name
+                ));
+            }
+        }
+        for cond in cat.conditionals.iter() {
+            let cond_desc = describe(&cond.condition)?;
+            if !layer_exists(&cond.condition) {
+                problems.push(format!(
+                    "Condition {} of category {} does not exist",
+                    cond_desc, cat.name
+                ));
+            } else if !rolled_before(&order, cond.condition.category, idx) {
+                problems.push(format!(
+                    "Condition {} is not rolled before category {}",
+                    cond_desc, cat.name
+                ));
+            }
+            if let Some(prob) = cond
+                .weights
+                .as_ref()
+                .and_then(|w| weight_problem(w, n, drawn))
+            {
+                problems.push(format!(
+                    "Weight table of category {} for condition {} {}",
+                    cat.name, cond_desc, prob
+                ));
+            }
+            if cond.forced.filter(|f| *f as usize >= n).is_some() {
+                problems.push(format!(
+                    "Forced variant of category {} for condition {} does not exist",
+                    cat.name, cond_desc
+                ));
+            }
+        }
+        if !scope.variants(idx) {
+            continue;
+        }
+        let var_store =
+            ReadonlyPrefixedStorage::multilevel(&[PREFIX_VARIANT, &idx.to_le_bytes()], storage);
+        let var_map =
+            ReadonlyPrefixedStorage::multilevel(&[PREFIX_VARIANT_MAP, &idx.to_le_bytes()], storage);
+        for var_idx in 0..n as u8 {
+            if let Some(var) = may_load::<Variant, _>(&var_store, &var_idx.to_le_bytes())? {
+                if may_load::<u8, _>(&var_map, var.name.as_bytes())? != Some(var_idx) {
+                    problems.push(format!(
+                        "Variant name {} of category {} does not map to index {}",
+                        var.name, cat.name, var_idx
+                    ));
+                }
+                check_render(storage, idx, &cat.name, &var, &mut problems);
+                if let Some(before) = var.render_before {
+                    if before == idx || var_cnt(before).is_none() {
+                        problems.push(format!(
+                            "Variant {} of category {} can not be drawn behind category {}",
+                            var.name,
+                            cat.name,
+                            describe_cat(&cats, before)
+                        ));
+                    }
+                }
+            } else {
+                problems.push(format!(
+                    "Variant {} of category {} is missing",
+                    var_idx, cat.name
+                ));
+            }
+        }
+    }
+    // check the dependencies, hiders, and exclusions
+    for (key, list_name) in [
+        (DEPENDENCIES_KEY, "Dependencies"),
+        (HIDERS_KEY, "Hiders"),
+        (EXCLUSIONS_KEY, "Exclusions"),
+    ]
+    .iter()
+    {
+        let list: Vec<StoredDependencies> = may_load(storage, key)?.unwrap_or_else(Vec::new);
+        for dep in list.iter().filter(|d| scope.entry(d)) {
+            let id_desc = describe(&dep.id)?;
+            if !layer_exists(&dep.id) {
+                problems.push(format!(
+                    "{} for {} refer to a missing variant",
+                    list_name, id_desc
+                ));
+            }
+            for layer in dep.correlated.iter() {
+                if !layer_exists(layer) {
+                    problems.push(format!(
+                        "{} for {} include missing variant {}",
+                        list_name,
+                        id_desc,
+                        describe(layer)?
+                    ));
+                }
+            }
+        }
+    }
+    // layers that are forced to appear together can not exclude each other
+    let exclusions: Vec<StoredDependencies> =
+        may_load(storage, EXCLUSIONS_KEY)?.unwrap_or_else(Vec::new);
+    if !exclusions.is_empty() {
+        let layer = |category: u8, variant: u8| StoredLayerId { category, variant };
+        let mut forced: Vec<(StoredLayerId, StoredLayerId)> = Vec::new();
+        for (idx, cat) in cats.iter().enumerate() {
+            let cat = if let Some(c) = cat.as_ref() {
+                c
+            } else {
+                continue;
+            };
+            let idx = idx as u8;
+            if let Some(arch) = archetype.as_ref() {
+                if let (Some(f), Some(c)) = (cat.forced_cyclops, arch.cyclops) {
+                    forced.push((layer(arch.eye_type, c), layer(idx, f)));
+                }
+                if let (Some(f), Some(j)) = (cat.forced_jawless, arch.jawless) {
+                    forced.push((layer(arch.jaw, j), layer(idx, f)));
+                }
+            }
+            for cond in cat.conditionals.iter() {
+                if let Some(f) = cond.forced {
+                    forced.push((cond.condition.clone(), layer(idx, f)));
+                }
+            }
+        }
+        let depends: Vec<StoredDependencies> =
+            may_load(storage, DEPENDENCIES_KEY)?.unwrap_or_else(Vec::new);
+        for dep in depends.iter() {
+            for (i, multi) in dep.correlated.iter().enumerate() {
+                forced.push((dep.id.clone(), multi.clone()));
+                for other in dep.correlated.iter().skip(i + 1) {
+                    forced.push((multi.clone(), other.clone()));
+                }
+            }
+        }
+        let excludes = |a: &StoredLayerId, b: &StoredLayerId| {
+            exclusions.iter().any(|e| {
+                (e.id == *a && e.correlated.contains(b)) || (e.id == *b && e.correlated.contains(a))
+            })
+        };
+        for (a, b) in forced.iter() {
+            if excludes(a, b) {
+                problems.push(format!(
+                    "Exclusions prevent {} from appearing with {}, but one forces the other",
+                    describe(a)?,
+                    describe(b)?
+                ));
+            }
+        }
+    }
+    Ok(problems)
+}
+
+/// adds a description of any problem rendering a variant's svg or animation
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `category` - index of the category the variant belongs to
+/// * `cat_name` - name of the category the variant belongs to
+/// * `var` - a reference to the variant
+/// * `problems` - a mutable reference to the list of problems found
+fn check_render<S: ReadonlyStorage>(
+    storage: &S,
+    category: u8,
+    cat_name: &str,
+    var: &Variant,
+    problems: &mut Vec<String>,
+) {
+    if var.svg.is_some() || var.recolor.is_some() {
+        if let Err(e) = variant_svg(storage, category, var) {
+            problems.push(format!(
+                "Svg of variant {} of category {} can not be rendered: {}",
+                var.name,
+                cat_name,
+                err_msg(e)
+            ));
+        }
+    }
+    if let Some(Err(e)) = var.animation.as_ref().map(|a| expand_fragments(storage, a)) {
+        problems.push(format!(
+            "Animation of variant {} of category {} can not be rendered: {}",
+            var.name,
+            cat_name,
+            err_msg(e)
+        ));
+    }
+}
+
+/// Returns Option<&'static str> describing a problem with a weight table
+///
+/// # Arguments
+///
+/// * `weights` - the weight table
+/// * `var_cnt` - number of variants in the table's category
+/// * `drawn` - true if variants are drawn from the table
+fn weight_problem(weights: &[u16], var_cnt: usize, drawn: bool) -> Option<&'static str> {
+    let total: u32 = weights.iter().map(|w| *w as u32).sum();
+    if weights.len() != var_cnt {
+        Some("has incorrect length")
+    } else if drawn && total == 0 {
+        Some("has no nonzero weights")
+    } else if drawn && total > u16::MAX as u32 {
+        Some("has weights totaling more than 65535")
+    } else {
+        None
+    }
+}
+
+/// Returns String of a category's name, or its index if it is missing
+///
+/// # Arguments
+///
+/// * `cats` - the categories by index
+/// * `category` - index of the category
+fn describe_cat(cats: &[Option<Category>], category: u8) -> String {
+    cats.get(category as usize)
+        .and_then(|c| c.as_ref())
+        .map_or_else(|| category.to_string(), |c| c.name.clone())
+}
+
+/// Returns String of the message of an StdError
+///
+/// # Arguments
+///
+/// * `err` - the error
+fn err_msg(err: StdError) -> String {
+    match err {
+        StdError::GenericErr { msg, .. } => msg,
+        e => e.to_string(),
+    }
+}